@@ -0,0 +1,147 @@
+use crate::error::{Error, ErrorKind};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Replace `@path` and `@shell:path` arguments by the arguments contained in
+/// those files, the way rustc does.
+///
+/// A `@path` file contains one argument per line. A `@shell:path` file
+/// contains arguments separated by whitespace, with shell-style quoting:
+/// `'single'` and `"double"` quotes, backslash escapes, and `#` comments.
+///
+/// Argument files may refer to further argument files, which are expanded
+/// too.
+///
+/// # Errors
+///
+/// Returns an error if an argument file cannot be read, contains an
+/// unterminated quote, or refers back to itself directly or indirectly.
+///
+/// ```no_run
+/// # fn main() -> Result<(), rustflags::Error> {
+/// let args = rustflags::expand_argfiles(std::env::args_os().skip(1))?;
+/// let flags = rustflags::from_encoded(&args.join(std::ffi::OsStr::new("\x1F")));
+/// # Ok(())
+/// # }
+/// ```
+pub fn expand_argfiles<I>(args: I) -> Result<Vec<OsString>, Error>
+where
+    I: IntoIterator,
+    I::Item: Into<OsString>,
+{
+    let mut expanded = Vec::new();
+    let mut stack = Vec::new();
+    for arg in args {
+        expand(arg.into(), &mut expanded, &mut stack)?;
+    }
+    Ok(expanded)
+}
+
+fn expand(
+    arg: OsString,
+    expanded: &mut Vec<OsString>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let Some(argfile) = arg.to_str().and_then(|arg| arg.strip_prefix('@')) else {
+        expanded.push(arg);
+        return Ok(());
+    };
+    let (path, shell) = match argfile.strip_prefix("shell:") {
+        Some(path) => (Path::new(path), true),
+        None => (Path::new(argfile), false),
+    };
+
+    let canonical = fs::canonicalize(path).map_err(|error| io_error(path, error))?;
+    if stack.contains(&canonical) {
+        return Err(argfile_error(
+            path,
+            "argument file includes itself".to_owned(),
+        ));
+    }
+    let contents = fs::read(path).map_err(|error| io_error(path, error))?;
+    let contents = String::from_utf8(contents)
+        .map_err(|_| argfile_error(path, "not valid UTF-8".to_owned()))?;
+    let args = if shell {
+        shell_split(&contents).map_err(|message| argfile_error(path, message))?
+    } else {
+        contents.lines().map(str::to_owned).collect()
+    };
+
+    stack.push(canonical);
+    for arg in args {
+        expand(OsString::from(arg), expanded, stack)?;
+    }
+    stack.pop();
+    Ok(())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> Error {
+    Error::new(ErrorKind::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+fn argfile_error(path: &Path, message: String) -> Error {
+    Error::new(ErrorKind::Argfile {
+        path: path.to_owned(),
+        message,
+    })
+}
+
+// POSIX shell word splitting, without expansions.
+fn shell_split(contents: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = contents.chars().peekable();
+    loop {
+        while chars.next_if(char::is_ascii_whitespace).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Ok(words);
+        };
+        if first == '\\' && chars.clone().nth(1) == Some('\n') {
+            // Line continuation between words.
+            chars.nth(1);
+            continue;
+        }
+        if first == '#' {
+            while chars.next_if(|&ch| ch != '\n').is_some() {}
+            continue;
+        }
+        let mut word = String::new();
+        while let Some(ch) = chars.next_if(|ch| !ch.is_ascii_whitespace()) {
+            match ch {
+                '\'' => loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err("unterminated single quote".to_owned()),
+                    }
+                },
+                '"' => loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('$' | '`' | '"' | '\\')) => word.push(ch),
+                            Some('\n') => {}
+                            Some(ch) => {
+                                word.push('\\');
+                                word.push(ch);
+                            }
+                            None => return Err("unterminated double quote".to_owned()),
+                        },
+                        Some(ch) => word.push(ch),
+                        None => return Err("unterminated double quote".to_owned()),
+                    }
+                },
+                '\\' => match chars.next() {
+                    Some('\n') => {}
+                    Some(ch) => word.push(ch),
+                    None => return Err("backslash at end of file".to_owned()),
+                },
+                _ => word.push(ch),
+            }
+        }
+        words.push(word);
+    }
+}
@@ -0,0 +1,387 @@
+//! Decode and inspect rustc flags.
+//!
+//! ```console
+//! $ rustflags [--format human|json|tokens] [FLAGS]
+//! $ rustflags encode FLAGS...
+//! $ rustflags check [--policy FILE] [--file PATH | --cargo-config] [--target TRIPLE] [FLAGS]
+//! $ rustflags diff OLD NEW
+//! ```
+//!
+//! Without FLAGS, the flags are read from CARGO_ENCODED_RUSTFLAGS if set,
+//! otherwise from RUSTFLAGS. An argument containing the ASCII unit separator
+//! ('\x1f') is decoded like CARGO_ENCODED_RUSTFLAGS, and anything else is
+//! split on whitespace like RUSTFLAGS.
+//!
+//! The `check` subcommand prints diagnostics for problematic flags and for
+//! flags denied by the policy file, and exits with status 1 if any of them is
+//! an error. `-Z` flags are an error if `$RUSTC -V` (default `rustc -V`)
+//! reports a stable or beta toolchain.
+//!
+//! The `diff` subcommand prints the settings that differ between two sets of
+//! flags, and exits with status 1 if there are any.
+
+#![allow(clippy::doc_markdown, clippy::uninlined_format_args)]
+
+use rustflags::{Channel, Diagnostic, Flag, Policy, Severity};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io::{self, Write as _};
+use std::process::{self, Command};
+
+const USAGE: &str = "\
+Usage: rustflags [--format human|json|tokens] [FLAGS]
+       rustflags encode FLAGS...
+       rustflags check [--policy FILE] [--file PATH | --cargo-config] [--target TRIPLE] [FLAGS]
+       rustflags diff OLD NEW
+
+Prints the flags parsed from FLAGS, or from CARGO_ENCODED_RUSTFLAGS or
+RUSTFLAGS in the environment if no FLAGS are given.
+
+The check subcommand reports problematic flags and flags denied by the policy
+FILE, and exits with status 1 if there are any errors. -Z flags are an error
+if $RUSTC (default: rustc) is a stable or beta toolchain. With --cargo-config,
+the flags are the ones Cargo would use for TRIPLE (default: the host) in the
+current directory.
+
+The diff subcommand prints the effective settings added (+), removed (-) or
+changed (~) going from OLD to NEW, and exits with status 1 if there are any.
+";
+
+#[derive(Copy, Clone)]
+enum Format {
+    Human,
+    Json,
+    Tokens,
+}
+
+fn main() {
+    let mut args = env::args_os().skip(1).peekable();
+
+    if args.peek().is_some_and(|arg| arg == "encode") {
+        args.next();
+        let rustflags = join(args).to_string_lossy().into_owned();
+        let tokens: Vec<&str> = rustflags.split_whitespace().collect();
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(tokens.join("\x1F").as_bytes());
+        let _ = stdout.write_all(b"\n");
+        return;
+    }
+
+    if args.peek().is_some_and(|arg| arg == "check") {
+        args.next();
+        check(args);
+    }
+
+    if args.peek().is_some_and(|arg| arg == "diff") {
+        args.next();
+        let (Some(old), Some(new), None) = (args.next(), args.next(), args.next()) else {
+            usage_error();
+        };
+        let diff = rustflags::diff(&decode(&old), &decode(&new));
+        let _ = io::stdout().lock().write_all(diff.to_string().as_bytes());
+        process::exit(i32::from(!diff.is_empty()));
+    }
+
+    let mut format = Format::Human;
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            print!("{}", USAGE);
+            return;
+        } else if arg == "--format" {
+            format = match args.next().as_ref().and_then(|arg| arg.to_str()) {
+                Some("human") => Format::Human,
+                Some("json") => Format::Json,
+                Some("tokens") => Format::Tokens,
+                _ => usage_error(),
+            };
+        } else if input.is_none() {
+            input = Some(arg);
+        } else {
+            usage_error();
+        }
+    }
+
+    let (flags, _source) = read_flags(input);
+
+    let mut output = String::new();
+    match format {
+        Format::Human => {
+            for flag in &flags {
+                let _ = writeln!(output, "{}", flag);
+            }
+        }
+        Format::Tokens => {
+            for arg in flags.into_iter().flatten() {
+                let _ = writeln!(output, "{}", arg.to_string_lossy());
+            }
+        }
+        Format::Json => {
+            output.push('[');
+            for (i, flag) in flags.into_iter().enumerate() {
+                output.push_str(if i == 0 { "\n  " } else { ",\n  " });
+                let _ = write!(
+                    output,
+                    "{{\"kind\": {}, \"args\": [",
+                    json_string(kind(&flag)),
+                );
+                for (i, arg) in flag.into_iter().enumerate() {
+                    if i > 0 {
+                        output.push_str(", ");
+                    }
+                    output.push_str(&json_string(&arg.to_string_lossy()));
+                }
+                output.push_str("]}");
+            }
+            output.push_str(if output.len() > 1 { "\n]\n" } else { "]\n" });
+        }
+    }
+    let _ = io::stdout().lock().write_all(output.as_bytes());
+}
+
+fn check(mut args: impl Iterator<Item = OsString>) -> ! {
+    let mut policy = None;
+    let mut file = None;
+    let mut cargo_config = false;
+    let mut target = None;
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            print!("{}", USAGE);
+            process::exit(0);
+        } else if arg == "--policy" {
+            policy = Some(args.next().unwrap_or_else(|| usage_error()));
+        } else if arg == "--file" {
+            file = Some(args.next().unwrap_or_else(|| usage_error()));
+        } else if arg == "--cargo-config" {
+            cargo_config = true;
+        } else if arg == "--target" {
+            let triple = args.next().and_then(|arg| arg.into_string().ok());
+            target = Some(triple.unwrap_or_else(|| usage_error()));
+        } else if input.is_none() {
+            input = Some(arg);
+        } else {
+            usage_error();
+        }
+    }
+    if u8::from(input.is_some()) + u8::from(file.is_some()) + u8::from(cargo_config) > 1 {
+        usage_error();
+    }
+
+    // Each flag paired with a description of where it came from.
+    let sourced: Vec<(Flag, String)> = if let Some(path) = file {
+        let contents = fs::read(&path).unwrap_or_else(|error| {
+            fatal(format_args!(
+                "failed to read {}: {}",
+                path.to_string_lossy(),
+                error
+            ))
+        });
+        let contents = String::from_utf8_lossy(&contents);
+        let flags = decode(OsStr::new(contents.trim_end_matches(['\r', '\n'])));
+        let source = path.to_string_lossy().into_owned();
+        flags
+            .into_iter()
+            .map(|flag| (flag, source.clone()))
+            .collect()
+    } else if cargo_config {
+        let cwd = env::current_dir().unwrap_or_else(|error| fatal(error));
+        let target = target.unwrap_or_else(host);
+        let report =
+            rustflags::flag_sources(cwd, &target, &[]).unwrap_or_else(|error| fatal(error));
+        report
+            .flags()
+            .map(|(flag, source)| (flag.clone(), source.to_string()))
+            .collect()
+    } else {
+        let (flags, source) = read_flags(input);
+        flags
+            .into_iter()
+            .map(|flag| (flag, source.clone()))
+            .collect()
+    };
+
+    let policy = match policy {
+        Some(path) => Policy::from_file(path).unwrap_or_else(|error| fatal(error)),
+        None => Policy::default(),
+    };
+
+    let flags: Vec<Flag> = sourced.iter().map(|(flag, _)| flag.clone()).collect();
+    let mut diagnostics = rustflags::check(&flags, channel());
+    diagnostics.extend(policy.check(&flags));
+    diagnostics.sort_by_key(|diagnostic| std::cmp::Reverse(diagnostic.severity));
+
+    let mut output = String::new();
+    for diagnostic in &diagnostics {
+        render(&mut output, diagnostic, &sourced);
+    }
+    let _ = io::stderr().lock().write_all(output.as_bytes());
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    process::exit(i32::from(errors > 0));
+}
+
+// Rustc-style: the message, where the flags come from, and the flags from
+// that source with the offending ones underlined.
+fn render(output: &mut String, diagnostic: &Diagnostic, sourced: &[(Flag, String)]) {
+    let _ = writeln!(output, "{}: {}", diagnostic.severity, diagnostic.message);
+    let Some(&first) = diagnostic.indices.first() else {
+        output.push('\n');
+        return;
+    };
+    let source = &sourced[first].1;
+    let mut line = String::new();
+    let mut carets = String::new();
+    for (i, (flag, flag_source)) in sourced.iter().enumerate() {
+        if flag_source != source {
+            continue;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        let rendered = flag.to_string();
+        let width = rendered.chars().count();
+        if diagnostic.indices.contains(&i) {
+            let column = line.chars().count();
+            let pad = column - carets.chars().count();
+            carets.extend(std::iter::repeat(' ').take(pad));
+            carets.extend(std::iter::repeat('^').take(width));
+        }
+        line.push_str(&rendered);
+    }
+    let _ = writeln!(output, "  --> {}", source);
+    let _ = writeln!(output, "   |");
+    let _ = writeln!(output, "   | {}", line);
+    let _ = writeln!(output, "   | {}", carets);
+    output.push('\n');
+}
+
+// Target triple of the rustc in PATH, or in $RUSTC.
+fn host() -> String {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc"));
+    let output = Command::new(&rustc)
+        .arg("-vV")
+        .output()
+        .unwrap_or_else(|error| fatal(format_args!("failed to run rustc: {}", error)));
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .unwrap_or_else(|| fatal("failed to determine host target from `rustc -vV`"))
+        .to_owned()
+}
+
+// Release channel of the rustc that Cargo would use, if it can be run.
+fn channel() -> Option<Channel> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc"));
+    let output = Command::new(&rustc).arg("-V").output().ok()?;
+    Channel::from_version(String::from_utf8_lossy(&output.stdout).trim_end())
+}
+
+fn fatal(message: impl Display) -> ! {
+    let _ = writeln!(io::stderr(), "error: {}", message);
+    process::exit(2);
+}
+
+// Flags from the command line argument, or else from the environment, along
+// with a description of where they came from.
+fn read_flags(input: Option<OsString>) -> (Vec<Flag>, String) {
+    if let Some(input) = input {
+        (decode(&input), "command line".to_owned())
+    } else if env::var_os("CARGO_ENCODED_RUSTFLAGS").is_some() {
+        let flags = rustflags::from_env().collect();
+        (
+            flags,
+            "environment variable CARGO_ENCODED_RUSTFLAGS".to_owned(),
+        )
+    } else {
+        let flags = from_rustflags(&env::var_os("RUSTFLAGS").unwrap_or_default());
+        (flags, "environment variable RUSTFLAGS".to_owned())
+    }
+}
+
+fn decode(input: &OsStr) -> Vec<Flag> {
+    if input.as_encoded_bytes().contains(&b'\x1F') {
+        rustflags::from_encoded(input).collect()
+    } else {
+        from_rustflags(input)
+    }
+}
+
+fn usage_error() -> ! {
+    let _ = io::stderr().write_all(USAGE.as_bytes());
+    process::exit(2);
+}
+
+fn join(args: impl Iterator<Item = OsString>) -> OsString {
+    let args: Vec<OsString> = args.collect();
+    args.join(OsStr::new(" "))
+}
+
+// Whitespace separated, like Cargo's treatment of RUSTFLAGS.
+fn from_rustflags(rustflags: &OsStr) -> Vec<Flag> {
+    let rustflags = rustflags.to_string_lossy();
+    let args: Vec<&str> = rustflags.split_whitespace().collect();
+    rustflags::from_encoded(OsStr::new(&args.join("\x1F"))).collect()
+}
+
+fn kind(flag: &Flag) -> &'static str {
+    match flag {
+        Flag::Help => "Help",
+        Flag::Cfg { .. } => "Cfg",
+        Flag::CheckCfg(_) => "CheckCfg",
+        Flag::LibrarySearchPath { .. } => "LibrarySearchPath",
+        Flag::Link { .. } => "Link",
+        Flag::CrateType(_) => "CrateType",
+        Flag::CrateName(_) => "CrateName",
+        Flag::Edition(_) => "Edition",
+        Flag::Emit(_) => "Emit",
+        Flag::Print(_) => "Print",
+        Flag::Out(_) => "Out",
+        Flag::OutDir(_) => "OutDir",
+        Flag::Explain(_) => "Explain",
+        Flag::Test => "Test",
+        Flag::Target(_) => "Target",
+        Flag::Allow(_) => "Allow",
+        Flag::Warn(_) => "Warn",
+        Flag::ForceWarn(_) => "ForceWarn",
+        Flag::Deny(_) => "Deny",
+        Flag::Forbid(_) => "Forbid",
+        Flag::CapLints(_) => "CapLints",
+        Flag::Codegen { .. } => "Codegen",
+        Flag::Version => "Version",
+        Flag::Verbose => "Verbose",
+        Flag::Extern { .. } => "Extern",
+        Flag::ExternLocation { .. } => "ExternLocation",
+        Flag::Sysroot(_) => "Sysroot",
+        Flag::Z(_) => "Z",
+        Flag::ErrorFormat(_) => "ErrorFormat",
+        Flag::Json(_) => "Json",
+        Flag::Color(_) => "Color",
+        Flag::RemapPathPrefix { .. } => "RemapPathPrefix",
+        _ => "Unknown",
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::from("\"");
+    for ch in string.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            _ if ch.is_control() => {
+                let _ = write!(json, "\\u{:04x}", ch as u32);
+            }
+            _ => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
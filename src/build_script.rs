@@ -0,0 +1,148 @@
+use crate::error::{Error, ErrorKind};
+use crate::parse::opt;
+use crate::string::EnvStr;
+use crate::Flag;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Parse the directives printed by a build script into the flags Cargo passes
+/// to rustc on its behalf.
+///
+/// Both the `cargo:KEY=VALUE` and `cargo::KEY=VALUE` forms are recognized.
+/// The following directives produce flags:
+///
+/// - `rustc-link-lib=[KIND[:MODIFIERS]=]NAME[:RENAME]` &rarr; [`Flag::Link`]
+/// - `rustc-link-search=[KIND=]PATH` &rarr; [`Flag::LibrarySearchPath`]
+/// - `rustc-flags=FLAGS` &rarr; [`Flag::Link`] and [`Flag::LibrarySearchPath`]
+/// - `rustc-cfg=KEY[="VALUE"]` &rarr; [`Flag::Cfg`]
+/// - `rustc-check-cfg=SPEC` &rarr; [`Flag::CheckCfg`]
+/// - `rustc-link-arg=FLAG` &rarr; `-C link-arg=FLAG`
+///
+/// Other lines, including directives that only apply to some of the package's
+/// targets such as `rustc-link-arg-bins`, are ignored.
+pub fn from_build_script_output(output: &str) -> Vec<Flag> {
+    let mut flags = Vec::new();
+
+    for line in output.lines() {
+        let Some(directive) = line
+            .strip_prefix("cargo::")
+            .or_else(|| line.strip_prefix("cargo:"))
+        else {
+            continue;
+        };
+        let Some((key, value)) = directive.split_once('=') else {
+            continue;
+        };
+        let value = value.trim_end_matches('\r');
+        let arg = EnvStr::new(OsStr::new(value));
+
+        let flag = match key {
+            "rustc-link-lib" => opt::link(arg),
+            "rustc-link-search" => opt::library_search_path(arg),
+            "rustc-cfg" => opt::cfg(arg),
+            "rustc-check-cfg" => opt::check_cfg(arg),
+            "rustc-link-arg" => Some(Flag::Codegen {
+                opt: "link-arg".to_owned(),
+                value: Some(value.to_owned()),
+            }),
+            "rustc-flags" => {
+                rustc_flags(value, &mut flags);
+                continue;
+            }
+            _ => continue,
+        };
+        flags.extend(flag);
+    }
+
+    flags
+}
+
+/// Parse the `output` file that Cargo saves for each build script run, for
+/// example `target/debug/build/foo-0123456789abcdef/output`.
+///
+/// See [`from_build_script_output`].
+///
+/// # Errors
+///
+/// Returns the I/O error if the file cannot be read.
+pub fn from_build_script_output_file(path: impl AsRef<Path>) -> io::Result<Vec<Flag>> {
+    let output = fs::read_to_string(path)?;
+    Ok(from_build_script_output(&output))
+}
+
+// Cargo only accepts -l and -L in `rustc-flags`, each with its argument either
+// attached or in the following whitespace separated word.
+fn rustc_flags(value: &str, flags: &mut Vec<Flag>) {
+    let mut words = value.split_whitespace();
+    while let Some(word) = words.next() {
+        let (constructor, arg): (fn(&EnvStr) -> Option<Flag>, &str) =
+            if let Some(arg) = word.strip_prefix("-l") {
+                (opt::link, arg)
+            } else if let Some(arg) = word.strip_prefix("-L") {
+                (opt::library_search_path, arg)
+            } else {
+                continue;
+            };
+        let arg = if arg.is_empty() {
+            match words.next() {
+                Some(arg) => arg,
+                None => break,
+            }
+        } else {
+            arg
+        };
+        flags.extend(constructor(EnvStr::new(OsStr::new(arg))));
+    }
+}
+
+/// Render flags as the build script directives that make Cargo pass the same
+/// flags to rustc, one `cargo::` directive per line.
+///
+/// This is the reverse of [`from_build_script_output`]. It supports `-l`,
+/// `-L`, `--cfg`, `--check-cfg`, and `-C link-arg`/`-C link-args`.
+///
+/// The `cargo::` syntax requires Cargo 1.77 or newer.
+///
+/// # Errors
+///
+/// Returns an error for any other flag, or for a path that is not valid
+/// UTF-8.
+pub fn to_build_script_output(flags: &[Flag]) -> Result<String, Error> {
+    let mut output = String::new();
+
+    for flag in flags {
+        let (key, values) = match flag {
+            Flag::Link { .. } => ("rustc-link-lib", vec![argument(flag)?]),
+            Flag::LibrarySearchPath { .. } => ("rustc-link-search", vec![argument(flag)?]),
+            Flag::Cfg { .. } => ("rustc-cfg", vec![argument(flag)?]),
+            Flag::CheckCfg(spec) => ("rustc-check-cfg", vec![spec.clone()]),
+            Flag::Codegen {
+                opt,
+                value: Some(value),
+            } if opt == "link-arg" => ("rustc-link-arg", vec![value.clone()]),
+            Flag::Codegen {
+                opt,
+                value: Some(value),
+            } if opt == "link-args" => (
+                "rustc-link-arg",
+                value.split_whitespace().map(str::to_owned).collect(),
+            ),
+            _ => return Err(Error::new(ErrorKind::NoDirective(flag.to_string()))),
+        };
+        for value in values {
+            let _ = writeln!(output, "cargo::{}={}", key, value);
+        }
+    }
+
+    Ok(output)
+}
+
+// The build script directive takes the same syntax as the flag's argument.
+fn argument(flag: &Flag) -> Result<String, Error> {
+    let arg = flag.clone().into_iter().nth(1).unwrap();
+    arg.into_string()
+        .map_err(|_| Error::new(ErrorKind::NotUtf8(flag.to_string())))
+}
@@ -0,0 +1,247 @@
+use crate::parse::option_name;
+use crate::{Category, Flag, RustFlags};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+
+/// Rewrite flags into a canonical form, so that sequences of flags with the
+/// same effect come out identical.
+///
+/// - `-O` and `-g` are spelled as `-C opt-level=2` and `-C debuginfo=2`,
+///   codegen and `-Z` option names use `-` rather than `_`, lint names use
+///   `_` rather than `-`, and boolean codegen and `-Z` values are spelled
+///   `yes` or `no`, with an option given without a value spelled `=yes`;
+/// - a codegen or `-Z` option, or a flag like `--target` or `--edition`,
+///   that is overridden by a later occurrence is dropped;
+/// - duplicate `--cfg`, `--check-cfg`, `--crate-type`, `--emit` and similar
+///   flags are dropped;
+/// - for each lint only the last level is kept, unless the lint is forbidden
+///   or force-warned: rustc does not let later flags override `--forbid` and
+///   `--force-warn`, so those are kept and other levels for the lint dropped;
+/// - duplicate `-L` flags are dropped, keeping the first.
+///
+/// Flags whose order does not matter come first, sorted. They are followed by
+/// the flags whose order matters, in their original relative order: lint
+/// levels, `-L`, `-l`, `--remap-path-prefix`, and the options that accumulate
+/// across occurrences, like `-C link-arg`, `-C target-feature` and
+/// `-Z crate-attr`.
+pub fn canonicalize(flags: &[Flag]) -> Vec<Flag> {
+    let flags: Vec<Flag> = flags.iter().map(normalize).collect();
+
+    let mut unordered = BTreeMap::new();
+    let mut ordered = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut last = BTreeMap::new();
+    let mut sticky = BTreeSet::new();
+    for (i, flag) in flags.iter().enumerate() {
+        if let Some(key) = override_key(flag) {
+            last.insert(key, i);
+        }
+        if let Flag::Forbid(name) | Flag::ForceWarn(name) = flag {
+            sticky.insert(name.as_str());
+        }
+    }
+
+    for (i, flag) in flags.iter().enumerate() {
+        if let Flag::Allow(name) | Flag::Warn(name) | Flag::Deny(name) = flag {
+            if sticky.contains(name.as_str()) {
+                continue;
+            }
+        }
+        let rendered = flag.to_string();
+        if let Some(key) = override_key(flag) {
+            if last[&key] == i {
+                if is_ordered(flag) {
+                    ordered.push(flag.clone());
+                } else {
+                    unordered.insert(rendered, flag.clone());
+                }
+            }
+        } else if is_ordered(flag) {
+            if is_cumulative(flag) || seen.insert(rendered) {
+                ordered.push(flag.clone());
+            }
+        } else {
+            unordered.insert(rendered, flag.clone());
+        }
+    }
+
+    unordered.into_values().chain(ordered).collect()
+}
+
+/// Hash of the flags that affect the output of compilation.
+///
+/// The fingerprint is computed over the [canonical form][canonicalize] of the
+/// flags, so spelling them differently, or reordering flags whose order has
+/// no effect, does not change it.
+/// Flags that only affect [diagnostics][Category::Diagnostics] are left out,
+/// such as lint levels, `--color`, `--error-format`, `--json` and `--verbose`.
+/// Flags not recognized by this crate, such as `--diagnostic-width`, are left
+/// out too.
+///
+/// The result is the 64-bit FNV-1a hash of the canonical flags encoded like
+/// CARGO_ENCODED_RUSTFLAGS. It is the same on every platform, and does not
+/// change between versions of this crate except in a breaking release.
+///
+/// ```no_run
+/// use std::env;
+/// use std::fs;
+/// use std::path::Path;
+///
+/// let fingerprint = rustflags::from_env().fingerprint().to_string();
+/// let stamp = Path::new(&env::var_os("OUT_DIR").unwrap()).join("rustflags-fingerprint");
+/// if fs::read_to_string(&stamp).ok().as_ref() != Some(&fingerprint) {
+///     // ... rebuild vendored C code ...
+///     fs::write(&stamp, fingerprint).unwrap();
+/// }
+/// ```
+pub fn fingerprint(flags: &[Flag]) -> u64 {
+    let relevant: Vec<Flag> = flags
+        .iter()
+        .filter(|flag| flag.categories() != [Category::Diagnostics])
+        .cloned()
+        .collect();
+    let encoded = crate::to_encoded(canonicalize(&relevant));
+
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = OFFSET_BASIS;
+    for &byte in encoded.as_encoded_bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
+impl RustFlags {
+    /// Rewrite the flags into a canonical form. See [`canonicalize`].
+    pub fn canonicalize(self) -> Vec<Flag> {
+        canonicalize(&self.collect::<Vec<Flag>>())
+    }
+
+    /// Hash of the flags that affect the output of compilation. See
+    /// [`fingerprint`].
+    pub fn fingerprint(self) -> u64 {
+        fingerprint(&self.collect::<Vec<Flag>>())
+    }
+}
+
+// Flags of which only the last occurrence takes effect.
+const SINGLE_VALUED: &[&str] = &[
+    "--cap-lints",
+    "--color",
+    "--crate-name",
+    "--edition",
+    "--error-format",
+    "--explain",
+    "--out-dir",
+    "--sysroot",
+    "--target",
+    "-o",
+];
+
+// Codegen and `-Z` options that accumulate across occurrences.
+const LIST_OPTIONS: &[&str] = &[
+    "crate-attr",
+    "link-arg",
+    "link-args",
+    "llvm-args",
+    "passes",
+    "pre-link-arg",
+    "pre-link-args",
+];
+
+// Spell equivalent codegen and `-Z` option values the same way. An option
+// without a value is the same as `yes`, except in lists.
+fn normalize_value(opt: &str, value: Option<&str>) -> Option<String> {
+    if is_list_option(opt) {
+        return value.map(str::to_owned);
+    }
+    let value = match (opt, value.unwrap_or("yes")) {
+        ("debuginfo", "0" | "none") => "0",
+        ("debuginfo", "1" | "limited") => "1",
+        ("debuginfo", "2" | "full") => "2",
+        (_, "y" | "yes" | "on" | "true") => "yes",
+        (_, "n" | "no" | "off" | "false") => "no",
+        (_, value) => value,
+    };
+    Some(value.to_owned())
+}
+
+pub(crate) fn normalize(flag: &Flag) -> Flag {
+    match flag {
+        Flag::Codegen { opt, value } => {
+            let opt = option_name(opt);
+            let value = normalize_value(&opt, value.as_deref());
+            Flag::Codegen { opt, value }
+        }
+        Flag::Z(option) => {
+            let (opt, value) = split_option(option);
+            let opt = option_name(opt);
+            Flag::Z(match normalize_value(&opt, value) {
+                Some(value) => format!("{}={}", opt, value),
+                None => opt,
+            })
+        }
+        Flag::Allow(name) => Flag::Allow(name.replace('-', "_")),
+        Flag::Warn(name) => Flag::Warn(name.replace('-', "_")),
+        Flag::ForceWarn(name) => Flag::ForceWarn(name.replace('-', "_")),
+        Flag::Deny(name) => Flag::Deny(name.replace('-', "_")),
+        Flag::Forbid(name) => Flag::Forbid(name.replace('-', "_")),
+        _ => flag.clone(),
+    }
+}
+
+// Identifies the setting controlled by a normalized flag of which only the
+// last occurrence takes effect.
+pub(crate) fn override_key(flag: &Flag) -> Option<String> {
+    match flag {
+        Flag::Codegen { opt, .. } if !is_list_option(opt) => Some(format!("-C {}", opt)),
+        Flag::Z(option) => {
+            let (opt, _value) = split_option(option);
+            (!is_list_option(opt)).then(|| format!("-Z {}", opt))
+        }
+        Flag::Allow(name) | Flag::Warn(name) | Flag::Deny(name) => Some(format!("lint {}", name)),
+        Flag::ForceWarn(name) => Some(format!("lint {} force-warn", name)),
+        Flag::Forbid(name) => Some(format!("lint {} forbid", name)),
+        _ => {
+            let args: Vec<OsString> = flag.clone().into_iter().collect();
+            let [arg, _value] = args.as_slice() else {
+                return None;
+            };
+            let arg = arg.to_str()?;
+            SINGLE_VALUED.contains(&arg).then(|| arg.to_owned())
+        }
+    }
+}
+
+fn is_ordered(flag: &Flag) -> bool {
+    match flag {
+        Flag::Allow(_)
+        | Flag::Warn(_)
+        | Flag::ForceWarn(_)
+        | Flag::Deny(_)
+        | Flag::Forbid(_)
+        | Flag::LibrarySearchPath { .. }
+        | Flag::Link { .. }
+        | Flag::RemapPathPrefix { .. } => true,
+        Flag::Codegen { opt, .. } => is_list_option(opt),
+        Flag::Z(option) => is_list_option(split_option(option).0),
+        _ => false,
+    }
+}
+
+// Ordered flags that take effect again when repeated.
+fn is_cumulative(flag: &Flag) -> bool {
+    matches!(flag, Flag::Link { .. } | Flag::Codegen { .. } | Flag::Z(_))
+}
+
+pub(crate) fn is_list_option(opt: &str) -> bool {
+    opt == "target-feature" || LIST_OPTIONS.contains(&opt)
+}
+
+pub(crate) fn split_option(option: &str) -> (&str, Option<&str>) {
+    match option.split_once('=') {
+        Some((opt, value)) => (opt, Some(value)),
+        None => (option, None),
+    }
+}
@@ -0,0 +1,210 @@
+// Evaluation of `cfg(...)` expressions, as used by Cargo config keys of the
+// form `target.'cfg(...)'.rustflags`, against the cfg values of a target.
+
+pub(crate) type Cfgs = Vec<(String, Option<String>)>;
+
+// Approximation of `rustc --print cfg --target TARGET` derived from the
+// components of the target triple alone, for when running rustc is not an
+// option.
+pub(crate) fn target_cfgs(target: &str) -> Cfgs {
+    let parts: Vec<&str> = target.split('-').collect();
+    let raw_arch = parts[0];
+
+    let (vendor, os, env) = match parts.as_slice() {
+        [_, os] => ("unknown", *os, ""),
+        [_, os @ ("linux" | "none" | "wasip1" | "wasip2"), env] => ("unknown", *os, *env),
+        [_, vendor, os] => (*vendor, *os, ""),
+        [_, vendor, os, env, ..] => (*vendor, *os, *env),
+        _ => ("unknown", "none", ""),
+    };
+
+    let arch = match raw_arch {
+        "i386" | "i586" | "i686" => "x86",
+        "arm64" | "arm64e" | "arm64_32" => "aarch64",
+        _ if raw_arch.starts_with("arm") || raw_arch.starts_with("thumb") => "arm",
+        _ if raw_arch.starts_with("riscv64") => "riscv64",
+        _ if raw_arch.starts_with("riscv32") => "riscv32",
+        "powerpc64le" => "powerpc64",
+        "mipsel" | "mipsisa32r6" | "mipsisa32r6el" => "mips",
+        "mips64el" | "mipsisa64r6" | "mipsisa64r6el" => "mips64",
+        "sparcv9" => "sparc64",
+        _ => raw_arch,
+    };
+
+    let os = match os {
+        "darwin" => "macos",
+        "wasip1" | "wasip2" => "wasi",
+        "linux" if env.starts_with("android") => "android",
+        _ => os,
+    };
+
+    let env = if env.starts_with("gnu") {
+        "gnu"
+    } else if env.starts_with("musl") {
+        "musl"
+    } else if env.starts_with("msvc") {
+        "msvc"
+    } else if env == "sgx" || env == "uclibc" || env == "newlib" || env == "ohos" {
+        env
+    } else {
+        ""
+    };
+
+    let pointer_width = match arch {
+        "x86_64" | "aarch64" | "riscv64" | "powerpc64" | "mips64" | "s390x" | "sparc64"
+        | "loongarch64" | "wasm64" | "bpf"
+            if raw_arch != "arm64_32" && !target.ends_with("gnux32") =>
+        {
+            "64"
+        }
+        "avr" | "msp430" => "16",
+        _ => "32",
+    };
+
+    let big_endian = matches!(
+        raw_arch,
+        "powerpc" | "powerpc64" | "s390x" | "sparc" | "sparc64"
+    ) || matches!(raw_arch, "mips" | "mips64" | "mipsisa32r6" | "mipsisa64r6")
+        || raw_arch.ends_with("eb")
+        || raw_arch.ends_with("_be");
+
+    let mut families = Vec::new();
+    match os {
+        "windows" => families.push("windows"),
+        "linux" | "android" | "macos" | "ios" | "tvos" | "watchos" | "visionos" | "freebsd"
+        | "netbsd" | "openbsd" | "dragonfly" | "solaris" | "illumos" | "haiku" | "fuchsia"
+        | "redox" | "aix" | "hurd" | "nto" | "emscripten" => families.push("unix"),
+        _ => {}
+    }
+    if arch.starts_with("wasm") {
+        families.push("wasm");
+    }
+
+    let mut cfgs = Cfgs::new();
+    let mut push = |name: &str, value: Option<&str>| {
+        cfgs.push((name.to_owned(), value.map(str::to_owned)));
+    };
+    for family in &families {
+        if *family != "wasm" {
+            push(family, None);
+        }
+        push("target_family", Some(family));
+    }
+    push("target_arch", Some(arch));
+    push("target_os", Some(os));
+    push("target_env", Some(env));
+    push("target_vendor", Some(vendor));
+    push("target_pointer_width", Some(pointer_width));
+    push(
+        "target_endian",
+        Some(if big_endian { "big" } else { "little" }),
+    );
+    cfgs
+}
+
+// Evaluate `cfg(PREDICATE)`. Returns None if the expression is malformed.
+pub(crate) fn matches(expr: &str, cfgs: &Cfgs) -> Option<bool> {
+    let inner = expr.trim().strip_prefix("cfg(")?.strip_suffix(')')?;
+    let tokens = tokenize(inner)?;
+    let mut pos = 0;
+    let result = predicate(&tokens, &mut pos, cfgs)?;
+    if pos == tokens.len() {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+#[derive(PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    String(&'a str),
+    Eq,
+    Comma,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let Some(ch) = rest.chars().next() else {
+            return Some(tokens);
+        };
+        let len = match ch {
+            '=' => {
+                tokens.push(Token::Eq);
+                1
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                1
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                1
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                1
+            }
+            '"' => {
+                let end = rest[1..].find('"')?;
+                tokens.push(Token::String(&rest[1..=end]));
+                end + 2
+            }
+            _ if ch.is_alphanumeric() || ch == '_' => {
+                let end = rest
+                    .find(|ch: char| !ch.is_alphanumeric() && ch != '_')
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Ident(&rest[..end]));
+                end
+            }
+            _ => return None,
+        };
+        rest = &rest[len..];
+    }
+}
+
+fn predicate(tokens: &[Token], pos: &mut usize, cfgs: &Cfgs) -> Option<bool> {
+    let Some(Token::Ident(name)) = tokens.get(*pos) else {
+        return None;
+    };
+    *pos += 1;
+    match (*name, tokens.get(*pos)) {
+        ("all" | "any" | "not", Some(Token::Open)) => {
+            *pos += 1;
+            let mut results = Vec::new();
+            while tokens.get(*pos) != Some(&Token::Close) {
+                results.push(predicate(tokens, pos, cfgs)?);
+                match tokens.get(*pos) {
+                    Some(Token::Comma) => *pos += 1,
+                    Some(Token::Close) => {}
+                    _ => return None,
+                }
+            }
+            *pos += 1;
+            match *name {
+                "all" => Some(results.iter().all(|&result| result)),
+                "any" => Some(results.iter().any(|&result| result)),
+                _ if results.len() == 1 => Some(!results[0]),
+                _ => None,
+            }
+        }
+        (_, Some(Token::Eq)) => {
+            let Some(Token::String(value)) = tokens.get(*pos + 1) else {
+                return None;
+            };
+            *pos += 2;
+            Some(cfgs.iter().any(|(cfg_name, cfg_value)| {
+                cfg_name == name && cfg_value.as_deref() == Some(*value)
+            }))
+        }
+        _ => Some(
+            cfgs.iter()
+                .any(|(cfg_name, cfg_value)| cfg_name == name && cfg_value.is_none()),
+        ),
+    }
+}
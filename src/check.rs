@@ -0,0 +1,293 @@
+use crate::parse::option_name;
+use crate::{CrateType, Flag, LintLevel, RustFlags};
+use std::cmp::Reverse;
+use std::fmt::{self, Display};
+
+/// Look for combinations of flags that are contradictory or likely to cause
+/// trouble.
+///
+/// The following are reported:
+///
+/// - errors:
+///   - `-Cprefer-dynamic` together with `-Ctarget-feature=+crt-static`,
+///   - more than one `--target` with different values,
+///   - `-Clto` together with `-Cembed-bitcode=no`;
+/// - warnings:
+///   - `-Cpanic=abort` when building a `proc-macro` crate,
+///   - `-Ctarget-cpu=native`, which makes binaries unfit for distribution to
+///     other machines,
+///   - `--cap-lints` below `deny` along with `-D` or `-F` lints, which it
+///     silently overrides;
+/// - notes:
+///   - `-Cpanic=abort` without `--target`, because Cargo also applies such
+///     rustflags to proc macros.
+///
+/// `-Z` flags, which only a nightly toolchain accepts, are an error if
+/// `channel` is stable or beta, and a note if the channel is not known.
+///
+/// Diagnostics are returned in order of decreasing severity.
+pub fn check(flags: &[Flag], channel: Option<Channel>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let prefer_dynamic = last_codegen(flags, "prefer-dynamic")
+        .filter(|(_, value)| value.map_or(true, is_enabled))
+        .map(|(i, _)| i);
+    let crt_static = last_target_feature(flags, "crt-static");
+    if let (Some(prefer_dynamic), Some((crt_static, true))) = (prefer_dynamic, crt_static) {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "`-C prefer-dynamic` cannot be combined with a statically linked C runtime",
+            flags,
+            &[prefer_dynamic, crt_static],
+        ));
+    }
+
+    let targets: Vec<usize> = indices(flags, |flag| matches!(flag, Flag::Target(_)));
+    if targets.iter().any(|&i| flags[i] != flags[targets[0]]) {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "conflicting `--target` flags",
+            flags,
+            &targets,
+        ));
+    }
+
+    let lto = last_codegen(flags, "lto").filter(|(_, value)| value.map_or(true, is_enabled));
+    let embed_bitcode = last_codegen(flags, "embed-bitcode")
+        .filter(|(_, value)| value.is_some_and(|value| !is_enabled(value)));
+    if let (Some((lto, _)), Some((embed_bitcode, _))) = (lto, embed_bitcode) {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "`-C lto` is incompatible with `-C embed-bitcode=no`",
+            flags,
+            &[embed_bitcode, lto],
+        ));
+    }
+
+    let panic_abort = last_codegen(flags, "panic")
+        .filter(|(_, value)| *value == Some("abort"))
+        .map(|(i, _)| i);
+    let proc_macro = indices(flags, |flag| *flag == Flag::CrateType(CrateType::ProcMacro));
+    if let Some(panic_abort) = panic_abort {
+        if let Some(&proc_macro) = proc_macro.first() {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "a proc macro built with `-C panic=abort` crashes the compiler if it panics",
+                flags,
+                &[proc_macro, panic_abort],
+            ));
+        } else if targets.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                Severity::Note,
+                "unless Cargo is given `--target`, `-C panic=abort` in rustflags applies to proc macros too",
+                flags,
+                &[panic_abort],
+            ));
+        }
+    }
+
+    let native = indices(flags, |flag| match flag {
+        Flag::Codegen {
+            opt,
+            value: Some(value),
+        } => option_name(opt) == "target-cpu" && value == "native",
+        _ => false,
+    });
+    if !native.is_empty() {
+        diagnostics.push(Diagnostic::new(
+            Severity::Warning,
+            "`-C target-cpu=native` produces binaries that may not run on other machines",
+            flags,
+            &native,
+        ));
+    }
+
+    let cap_lints = flags
+        .iter()
+        .rposition(|flag| matches!(flag, Flag::CapLints(LintLevel::Allow | LintLevel::Warn)));
+    if let Some(cap_lints) = cap_lints {
+        let mut denied = indices(flags, |flag| {
+            matches!(flag, Flag::Deny(_) | Flag::Forbid(_))
+        });
+        if !denied.is_empty() {
+            denied.insert(0, cap_lints);
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "`--cap-lints` prevents denied lints from causing errors",
+                flags,
+                &denied,
+            ));
+        }
+    }
+
+    let unstable = indices(flags, |flag| matches!(flag, Flag::Z(_)));
+    let unstable_diagnostic = match channel {
+        Some(Channel::Stable) => Some((
+            Severity::Error,
+            "`-Z` flags are not accepted by a stable toolchain",
+        )),
+        Some(Channel::Beta) => Some((
+            Severity::Error,
+            "`-Z` flags are not accepted by a beta toolchain",
+        )),
+        Some(Channel::Nightly | Channel::Dev) => None,
+        None => Some((
+            Severity::Note,
+            "`-Z` flags are only accepted by a nightly toolchain",
+        )),
+    };
+    if !unstable.is_empty() {
+        if let Some((severity, message)) = unstable_diagnostic {
+            diagnostics.push(Diagnostic::new(severity, message, flags, &unstable));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| Reverse(diagnostic.severity));
+    diagnostics
+}
+
+impl RustFlags {
+    /// Look for combinations of flags that are contradictory or likely to
+    /// cause trouble. See [`check`].
+    pub fn check(self, channel: Option<Channel>) -> Vec<Diagnostic> {
+        check(&self.collect::<Vec<Flag>>(), channel)
+    }
+}
+
+/// **Release channel of a Rust toolchain**
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Channel {
+    /// A stable release, like `1.80.0`.
+    Stable,
+    /// A beta release, like `1.81.0-beta.3`.
+    Beta,
+    /// A nightly build, like `1.82.0-nightly`.
+    Nightly,
+    /// A locally built compiler, like `1.82.0-dev`.
+    Dev,
+}
+
+impl Channel {
+    /// Determine the channel from the output of `rustc -V`, such as
+    /// `rustc 1.80.0-nightly (ada5e2c7b 2024-05-31)`.
+    pub fn from_version(version: &str) -> Option<Self> {
+        let version = version.strip_prefix("rustc ")?.split(' ').next()?;
+        match version.split_once('-') {
+            None => Some(Channel::Stable),
+            Some((_, pre)) if pre.starts_with("beta") => Some(Channel::Beta),
+            Some((_, "nightly")) => Some(Channel::Nightly),
+            Some((_, "dev")) => Some(Channel::Dev),
+            Some(_) => None,
+        }
+    }
+}
+
+/// **Problem found by [`check`]**
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+
+    /// Description of the problem.
+    pub message: String,
+
+    /// The offending flags.
+    pub flags: Vec<Flag>,
+
+    /// Position of each of the offending flags within the checked flags.
+    pub indices: Vec<usize>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        severity: Severity,
+        message: &str,
+        flags: &[Flag],
+        indices: &[usize],
+    ) -> Self {
+        Diagnostic {
+            severity,
+            message: message.to_owned(),
+            flags: indices.iter().map(|&i| flags[i].clone()).collect(),
+            indices: indices.to_vec(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {}", self.severity, self.message)?;
+        for (i, flag) in self.flags.iter().enumerate() {
+            formatter.write_str(if i == 0 { " (" } else { ", " })?;
+            write!(formatter, "`{}`", flag)?;
+        }
+        if !self.flags.is_empty() {
+            formatter.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Severity of a [`Diagnostic`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing about, but harmless by itself.
+    Note,
+    /// Likely unintended.
+    Warning,
+    /// Rejected by rustc or certain to produce a broken build.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+fn indices(flags: &[Flag], predicate: impl Fn(&Flag) -> bool) -> Vec<usize> {
+    (0..flags.len()).filter(|&i| predicate(&flags[i])).collect()
+}
+
+fn last_codegen<'a>(flags: &'a [Flag], name: &str) -> Option<(usize, Option<&'a str>)> {
+    flags
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, flag)| match flag {
+            Flag::Codegen { opt, value } if option_name(opt) == name => Some((i, value.as_deref())),
+            _ => None,
+        })
+}
+
+// Whether the last mention of `feature` in `-Ctarget-feature` enables it.
+fn last_target_feature(flags: &[Flag], feature: &str) -> Option<(usize, bool)> {
+    let mut last = None;
+    for (i, flag) in flags.iter().enumerate() {
+        if let Flag::Codegen {
+            opt,
+            value: Some(value),
+        } = flag
+        {
+            if option_name(opt) == "target-feature" {
+                for entry in value.split(',') {
+                    if entry.get(1..) == Some(feature) {
+                        last = Some((i, entry.starts_with('+')));
+                    }
+                }
+            }
+        }
+    }
+    last
+}
+
+// Boolean codegen option values accepted by rustc.
+fn is_enabled(value: &str) -> bool {
+    !matches!(value, "n" | "no" | "off" | "false")
+}
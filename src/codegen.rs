@@ -0,0 +1,32 @@
+use crate::parse::option_name;
+use crate::Flag;
+
+/// Arguments passed to LLVM by all occurrences of `-Cllvm-args`.
+///
+/// Rustc splits each occurrence on whitespace and appends the pieces to the
+/// arguments from previous occurrences, so the result is suitable for passing
+/// along to another LLVM-based tool, for example as `clang -mllvm ARG`.
+pub fn llvm_args(flags: &[Flag]) -> Vec<String> {
+    list_option(flags, "llvm-args")
+}
+
+/// Extra LLVM passes requested by all occurrences of `-Cpasses`.
+pub fn passes(flags: &[Flag]) -> Vec<String> {
+    list_option(flags, "passes")
+}
+
+fn list_option(flags: &[Flag], name: &str) -> Vec<String> {
+    let mut list = Vec::new();
+    for flag in flags {
+        if let Flag::Codegen {
+            opt,
+            value: Some(value),
+        } = flag
+        {
+            if option_name(opt) == name {
+                list.extend(value.split_whitespace().map(str::to_owned));
+            }
+        }
+    }
+    list
+}
@@ -0,0 +1,575 @@
+use crate::cfg;
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::error::{Error, ErrorKind};
+use crate::string;
+use crate::toml::{self, Table, Value};
+use crate::{from_encoded, Flag, RustFlags};
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Compute the flags Cargo would pass to rustc when building for `target`
+/// from within the directory `cwd`, without relying on Cargo to provide
+/// CARGO_ENCODED_RUSTFLAGS.
+///
+/// This is intended for tools that run outside of a build script, such as
+/// xtasks and test harnesses. The first of the following sources that is
+/// present determines the flags, following Cargo's own precedence:
+///
+/// 1. the CARGO_ENCODED_RUSTFLAGS environment variable,
+/// 2. the RUSTFLAGS environment variable,
+/// 3. `target.<triple>.rustflags` together with every matching
+///    `target.<cfg>.rustflags`,
+/// 4. `build.rustflags`.
+///
+/// Config values are read from `.cargo/config.toml` in `cwd` and each of its
+/// parent directories, then from `$CARGO_HOME/config.toml`, with arrays from
+/// all files concatenated as Cargo does. The environment variables
+/// `CARGO_TARGET_<TRIPLE>_RUSTFLAGS` and `CARGO_BUILD_RUSTFLAGS` take part as
+/// the highest precedence config values.
+///
+/// Cfg expressions are evaluated against cfg values inferred from the target
+/// triple, which covers the commonly used `target_arch`, `target_os`,
+/// `target_env`, `target_family`, `target_vendor`, `target_pointer_width` and
+/// `target_endian` but not `target_feature`.
+///
+/// # Errors
+///
+/// Returns an error if a config file cannot be read or is not valid TOML, if
+/// a `rustflags` value is neither a string nor an array of strings or is a
+/// string in one file and an array in another, or if one of the environment
+/// variables involved is not valid UTF-8 or not a valid value.
+pub fn from_cargo_config(cwd: impl AsRef<Path>, target: &str) -> Result<RustFlags, Error> {
+    from_cargo_config_with(cwd, target, ProcessEnv)
+}
+
+/// Compute the flags Cargo would pass to rustc, like [`from_cargo_config`],
+/// reading environment variables from `env`.
+///
+/// # Errors
+///
+/// Same as [`from_cargo_config`].
+pub fn from_cargo_config_with(
+    cwd: impl AsRef<Path>,
+    target: &str,
+    env: impl EnvSource,
+) -> Result<RustFlags, Error> {
+    let report = flag_sources_with(cwd, target, &[], env)?;
+    let mut encoded = OsString::new();
+    for sourced in report.sources.iter().filter(|sourced| sourced.used) {
+        for arg in &sourced.args {
+            if !encoded.is_empty() {
+                encoded.push("\x1F");
+            }
+            encoded.push(arg);
+        }
+    }
+    Ok(from_encoded(&encoded))
+}
+
+/// Determine where each of the flags seen by rustc comes from.
+///
+/// This performs the same lookup as [`from_cargo_config`], additionally
+/// taking into account `--config` arguments given on the Cargo command line.
+/// Each element of `cli_config` is either a `KEY=VALUE` pair in TOML syntax,
+/// like `build.rustflags=["-Ctarget-cpu=native"]`, or the path of an extra
+/// config file.
+///
+/// The resulting report lists every source that defines rustflags, including
+/// the ones Cargo ignores because a source of higher precedence is present.
+///
+/// # Errors
+///
+/// Same as [`from_cargo_config`], and additionally if an element of
+/// `cli_config` is neither a valid `KEY=VALUE` pair nor a readable config
+/// file.
+pub fn flag_sources(
+    cwd: impl AsRef<Path>,
+    target: &str,
+    cli_config: &[&str],
+) -> Result<FlagReport, Error> {
+    flag_sources_with(cwd, target, cli_config, ProcessEnv)
+}
+
+/// Determine where each of the flags seen by rustc comes from, like
+/// [`flag_sources`], reading environment variables from `env`.
+///
+/// # Errors
+///
+/// Same as [`flag_sources`].
+#[allow(clippy::needless_pass_by_value)]
+pub fn flag_sources_with(
+    cwd: impl AsRef<Path>,
+    target: &str,
+    cli_config: &[&str],
+    env: impl EnvSource,
+) -> Result<FlagReport, Error> {
+    let env: &dyn EnvSource = &env;
+    let mut candidates = Vec::new();
+
+    if let Some(encoded) = env.var_os("CARGO_ENCODED_RUSTFLAGS") {
+        let args = string::split_encoded(&encoded);
+        let source = FlagSource::Env("CARGO_ENCODED_RUSTFLAGS".to_owned());
+        candidates.push((true, vec![Segment { source, args }]));
+    }
+
+    if let Some(rustflags) = env.var_os("RUSTFLAGS") {
+        let rustflags = env_str("RUSTFLAGS", &rustflags)?;
+        let source = FlagSource::Env("RUSTFLAGS".to_owned());
+        let args = rustflags.split_whitespace().map(OsString::from).collect();
+        candidates.push((true, vec![Segment { source, args }]));
+    }
+
+    let layers = config_layers(cwd.as_ref(), cli_config, env)?;
+
+    let mut target_segments = Vec::new();
+    let triple_env = format!(
+        "CARGO_TARGET_{}_RUSTFLAGS",
+        target.to_uppercase().replace(['-', '.'], "_"),
+    );
+    target_segments.extend(lookup_list(
+        &layers,
+        &["target", target, "rustflags"],
+        Some(&triple_env),
+        env,
+    )?);
+    let cfgs = cfg::target_cfgs(target);
+    for key in cfg_keys(&layers) {
+        if cfg::matches(&key, &cfgs) == Some(true) {
+            let path = ["target", &key, "rustflags"];
+            target_segments.extend(lookup_list(&layers, &path, None, env)?);
+        }
+    }
+    candidates.push((false, target_segments));
+
+    let build_segments = lookup_list(
+        &layers,
+        &["build", "rustflags"],
+        Some("CARGO_BUILD_RUSTFLAGS"),
+        env,
+    )?;
+    candidates.push((false, build_segments));
+
+    let mut sources = Vec::new();
+    let mut used = true;
+    // Environment variables take effect even when empty, whereas config values
+    // only count if they contribute at least one flag.
+    for (set, segments) in candidates {
+        let present = set || segments.iter().any(|segment| !segment.args.is_empty());
+        for segment in segments {
+            let flags = from_args(&segment.args).collect();
+            sources.push(SourcedFlags {
+                source: segment.source,
+                flags,
+                used: used && present,
+                args: segment.args,
+            });
+        }
+        if present {
+            used = false;
+        }
+    }
+
+    Ok(FlagReport { sources })
+}
+
+/// **Origin of rustflags in Cargo's configuration**
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FlagSource {
+    /// An environment variable, such as `RUSTFLAGS` or
+    /// `CARGO_BUILD_RUSTFLAGS`.
+    Env(String),
+
+    /// A key in a Cargo config file, such as
+    /// `target.x86_64-unknown-linux-gnu.rustflags`.
+    ConfigFile { path: PathBuf, key: String },
+
+    /// A `--config KEY=VALUE` argument on the Cargo command line.
+    CommandLine { config: String, key: String },
+}
+
+impl Display for FlagSource {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlagSource::Env(name) => write!(formatter, "environment variable {}", name),
+            FlagSource::ConfigFile { path, key } => {
+                write!(formatter, "`{}` in {}", key, path.display())
+            }
+            FlagSource::CommandLine { config, key: _ } => {
+                write!(formatter, "--config {}", config)
+            }
+        }
+    }
+}
+
+/// **Flags grouped by where they come from**
+///
+/// Returned by [`flag_sources`].
+#[derive(Clone, Debug)]
+pub struct FlagReport {
+    sources: Vec<SourcedFlags>,
+}
+
+/// **Flags defined by one source**
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SourcedFlags {
+    /// Where the flags are defined.
+    pub source: FlagSource,
+
+    /// The flags defined by this source.
+    pub flags: Vec<Flag>,
+
+    /// Whether Cargo uses these flags, as opposed to ignoring them in favor of
+    /// a source of higher precedence.
+    pub used: bool,
+
+    args: Vec<OsString>,
+}
+
+impl FlagReport {
+    /// Every source that defines rustflags, in the order their flags are
+    /// passed to rustc, including ignored sources.
+    pub fn sources(&self) -> &[SourcedFlags] {
+        &self.sources
+    }
+
+    /// The flags Cargo passes to rustc, each with its source.
+    pub fn flags(&self) -> impl Iterator<Item = (&Flag, &FlagSource)> {
+        self.sources
+            .iter()
+            .filter(|sourced| sourced.used)
+            .flat_map(|sourced| {
+                sourced
+                    .flags
+                    .iter()
+                    .map(move |flag| (flag, &sourced.source))
+            })
+    }
+}
+
+impl Display for FlagReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for sourced in &self.sources {
+            if !first {
+                writeln!(formatter)?;
+            }
+            first = false;
+            write!(formatter, "{}", sourced.source)?;
+            if !sourced.used {
+                write!(formatter, " (ignored)")?;
+            }
+            writeln!(formatter, ":")?;
+            for flag in &sourced.flags {
+                writeln!(formatter, "    {}", flag)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Segment {
+    source: FlagSource,
+    args: Vec<OsString>,
+}
+
+enum Origin {
+    File(PathBuf),
+    CommandLineFile(PathBuf),
+    CommandLine(String),
+}
+
+// A config file or `--config` argument. Layers are ordered from lowest to
+// highest precedence, with environment variables taking effect between the
+// config files and the command line.
+struct Layer {
+    origin: Origin,
+    table: Table,
+}
+
+impl Layer {
+    fn source(&self, key: &str) -> FlagSource {
+        match &self.origin {
+            Origin::File(path) | Origin::CommandLineFile(path) => FlagSource::ConfigFile {
+                path: path.clone(),
+                key: key.to_owned(),
+            },
+            Origin::CommandLine(config) => FlagSource::CommandLine {
+                config: config.clone(),
+                key: key.to_owned(),
+            },
+        }
+    }
+}
+
+fn config_layers(
+    cwd: &Path,
+    cli_config: &[&str],
+    env: &dyn EnvSource,
+) -> Result<Vec<Layer>, Error> {
+    let mut layers = Vec::new();
+    for file in config_files(cwd, env)? {
+        layers.push(Layer {
+            origin: Origin::File(file.path),
+            table: file.table,
+        });
+    }
+    for config in cli_config {
+        let path = Path::new(config);
+        let layer = if !config.contains('=') || path.is_file() {
+            Layer {
+                origin: Origin::CommandLineFile(path.to_owned()),
+                table: read_config(path)?,
+            }
+        } else {
+            let table = toml::parse(config).map_err(|error| {
+                Error::new(ErrorKind::Config {
+                    path: PathBuf::from("--config"),
+                    message: format!("{}: {}", config, error.message),
+                })
+            })?;
+            Layer {
+                origin: Origin::CommandLine((*config).to_owned()),
+                table,
+            }
+        };
+        layers.push(layer);
+    }
+    Ok(layers)
+}
+
+pub(crate) struct ConfigFile {
+    pub path: PathBuf,
+    pub table: Table,
+}
+
+// All config files that apply to `cwd`, from lowest to highest precedence.
+pub(crate) fn config_files(cwd: &Path, env: &dyn EnvSource) -> Result<Vec<ConfigFile>, Error> {
+    let mut paths = Vec::new();
+    for dir in cwd.ancestors() {
+        paths.extend(config_file_in(&dir.join(".cargo")));
+    }
+    if let Some(cargo_home) = cargo_home(env) {
+        if let Some(path) = config_file_in(&cargo_home) {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for path in paths.into_iter().rev() {
+        let table = read_config(&path)?;
+        files.push(ConfigFile { path, table });
+    }
+    Ok(files)
+}
+
+pub(crate) fn read_config(path: &Path) -> Result<Table, Error> {
+    let contents = fs::read_to_string(path).map_err(|error| {
+        Error::new(ErrorKind::Io {
+            path: path.to_owned(),
+            error,
+        })
+    })?;
+    toml::parse(&contents).map_err(|error| {
+        Error::new(ErrorKind::Config {
+            path: path.to_owned(),
+            message: error.to_string(),
+        })
+    })
+}
+
+// Cargo prefers the legacy extensionless file if both exist.
+fn config_file_in(dir: &Path) -> Option<PathBuf> {
+    let legacy = dir.join("config");
+    if legacy.is_file() {
+        return Some(legacy);
+    }
+    let path = dir.join("config.toml");
+    if path.is_file() {
+        return Some(path);
+    }
+    None
+}
+
+fn cargo_home(env: &dyn EnvSource) -> Option<PathBuf> {
+    if let Some(cargo_home) = env.var_os("CARGO_HOME") {
+        return Some(PathBuf::from(cargo_home));
+    }
+    let home = env.var_os("HOME").or_else(|| env.var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(".cargo"))
+}
+
+// Every `target.<cfg>` key across all config layers.
+fn cfg_keys(layers: &[Layer]) -> Vec<String> {
+    let mut keys = Vec::new();
+    for layer in layers {
+        let Some(target) = layer.table.get("target").and_then(Value::as_table) else {
+            continue;
+        };
+        for key in target.keys() {
+            if key.starts_with("cfg(") && !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+    }
+    keys.sort();
+    keys
+}
+
+pub(crate) fn get<'a>(table: &'a Table, path: &[&str]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+// Look up a config value that is either a whitespace separated string or an
+// array of strings. Arrays from different layers are concatenated, lower
+// precedence first, while a string replaces whatever came before it. Like
+// Cargo, a string in one config layer and an array in another cannot be
+// merged.
+fn lookup_list(
+    layers: &[Layer],
+    path: &[&str],
+    env_var: Option<&str>,
+    env: &dyn EnvSource,
+) -> Result<Vec<Segment>, Error> {
+    let key = path.join(".");
+    let mut segments = Vec::new();
+    let mut merged_kind = None;
+
+    let (files, cli): (Vec<&Layer>, Vec<&Layer>) = layers
+        .iter()
+        .partition(|layer| matches!(layer.origin, Origin::File(_)));
+
+    for layer in files {
+        if let Some(value) = get(&layer.table, path) {
+            let source = layer.source(&key);
+            merge_kind(&mut merged_kind, value, &key, &source)?;
+            push_list(&mut segments, value, source, &key)?;
+        }
+    }
+
+    if let Some(env_var) = env_var {
+        if let Some(value) = env.var_os(env_var) {
+            let value = env_str(env_var, &value)?;
+            let value = if value.trim_start().starts_with('[') {
+                toml::parse_value(value).map_err(|error| {
+                    Error::new(ErrorKind::Env {
+                        name: env_var.to_owned(),
+                        message: error.message,
+                    })
+                })?
+            } else {
+                Value::Array(
+                    value
+                        .split_whitespace()
+                        .map(|arg| Value::String(arg.to_owned()))
+                        .collect(),
+                )
+            };
+            let source = FlagSource::Env(env_var.to_owned());
+            push_list(&mut segments, &value, source, &key)?;
+        }
+    }
+
+    for layer in cli {
+        if let Some(value) = get(&layer.table, path) {
+            let source = layer.source(&key);
+            merge_kind(&mut merged_kind, value, &key, &source)?;
+            push_list(&mut segments, value, source, &key)?;
+        }
+    }
+
+    Ok(segments)
+}
+
+fn merge_kind(
+    merged_kind: &mut Option<&'static str>,
+    value: &Value,
+    key: &str,
+    source: &FlagSource,
+) -> Result<(), Error> {
+    let kind = match value {
+        Value::String(_) => "string",
+        _ => "array",
+    };
+    match merged_kind {
+        Some(merged_kind) if *merged_kind != kind => {
+            let message = format!(
+                "failed to merge `{}`: expected {}, but found {}",
+                key, merged_kind, kind,
+            );
+            Err(source_error(source, message))
+        }
+        _ => {
+            *merged_kind = Some(kind);
+            Ok(())
+        }
+    }
+}
+
+fn push_list(
+    segments: &mut Vec<Segment>,
+    value: &Value,
+    source: FlagSource,
+    key: &str,
+) -> Result<(), Error> {
+    let list = string_list(value).ok_or_else(|| {
+        let message = format!("expected a string or array of strings for `{}`", key);
+        source_error(&source, message)
+    })?;
+    if let Value::String(_) = value {
+        segments.clear();
+    }
+    segments.push(Segment {
+        source,
+        args: list.into_iter().map(OsString::from).collect(),
+    });
+    Ok(())
+}
+
+fn source_error(source: &FlagSource, message: String) -> Error {
+    Error::new(match source {
+        FlagSource::ConfigFile { path, .. } => ErrorKind::Config {
+            path: path.clone(),
+            message,
+        },
+        FlagSource::CommandLine { config, .. } => ErrorKind::Config {
+            path: PathBuf::from("--config"),
+            message: format!("{}: {}", config, message),
+        },
+        FlagSource::Env(name) => ErrorKind::Env {
+            name: name.clone(),
+            message,
+        },
+    })
+}
+
+pub(crate) fn string_list(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(string) => Some(string.split_whitespace().map(str::to_owned).collect()),
+        Value::Array(array) => array
+            .iter()
+            .map(|value| value.as_str().map(str::to_owned))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn env_str<'a>(name: &str, value: &'a OsStr) -> Result<&'a str, Error> {
+    value
+        .to_str()
+        .ok_or_else(|| Error::new(ErrorKind::NotUtf8(name.to_owned())))
+}
+
+fn from_args(args: &[OsString]) -> RustFlags {
+    from_encoded(&args.join(OsStr::new("\x1F")))
+}
@@ -0,0 +1,241 @@
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::parse::option_name;
+use crate::{from_encoded, Flag};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+
+/// **Everything Cargo tells a build script about the upcoming compilation**
+///
+/// Cargo describes the build to build scripts through a number of environment
+/// variables besides CARGO_ENCODED_RUSTFLAGS. Some of them are derived from
+/// the profile without regard for rustflags, even though rustflags are passed
+/// to rustc after the profile's options and therefore override them. The
+/// fields of `BuildContext` hold the effective values with rustflags taken
+/// into account, and every disagreement is recorded in `conflicts`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct BuildContext {
+    /// `TARGET`
+    pub target: Option<String>,
+
+    /// `HOST`
+    pub host: Option<String>,
+
+    /// `PROFILE`, which is either `debug` or `release`.
+    pub profile: Option<String>,
+
+    /// `OPT_LEVEL`, or the last `-Copt-level` in rustflags.
+    pub opt_level: Option<String>,
+
+    /// `DEBUG`, or whether the last `-Cdebuginfo` in rustflags enables
+    /// debuginfo.
+    pub debug: Option<bool>,
+
+    /// Target features enabled for the target, from
+    /// `CARGO_CFG_TARGET_FEATURE`, with the features enabled (`+`) or
+    /// disabled (`-`) by `-Ctarget-feature` in rustflags applied. Features
+    /// implied by an enabled feature are not added.
+    pub target_features: Vec<String>,
+
+    /// `CARGO_CFG_PANIC`, or the last `-Cpanic` in rustflags.
+    pub panic: Option<String>,
+
+    /// `RUSTC`
+    pub rustc: Option<PathBuf>,
+
+    /// `RUSTC_LINKER`, or the last `-Clinker` in rustflags.
+    pub linker: Option<PathBuf>,
+
+    /// Flags from CARGO_ENCODED_RUSTFLAGS.
+    pub flags: Vec<Flag>,
+
+    /// Environment variables whose value is overridden or contradicted by
+    /// rustflags.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// **Disagreement between a build script environment variable and
+/// rustflags**
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Conflict {
+    /// Name of the environment variable.
+    pub var: String,
+
+    /// Value of the environment variable.
+    pub value: String,
+
+    /// The flag that disagrees with it.
+    pub flag: Flag,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}={} conflicts with `{}` in rustflags",
+            self.var, self.value, self.flag,
+        )
+    }
+}
+
+impl BuildContext {
+    /// Read the build script environment of the current process.
+    pub fn from_env() -> Self {
+        BuildContext::from_env_with(ProcessEnv)
+    }
+
+    /// Read the build script environment from `env`.
+    ///
+    /// This allows testing build scripts without modifying the environment of
+    /// the test process.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_env_with(env: impl EnvSource) -> Self {
+        let var = |key: &str| env.var_os(key);
+        let string = |key: &str| env.var(key);
+        let flags: Vec<Flag> = match var("CARGO_ENCODED_RUSTFLAGS") {
+            Some(encoded) => from_encoded(&encoded).collect(),
+            None => Vec::new(),
+        };
+        let mut conflicts = Vec::new();
+
+        let mut target = string("TARGET");
+        let flag_target = last(&flags, |flag| match flag {
+            Flag::Target(value) => Some((flag, value)),
+            _ => None,
+        });
+        if let Some((flag, value)) = flag_target {
+            match &target {
+                Some(env_value) => {
+                    if env_value != value {
+                        conflicts.push(conflict("TARGET", env_value, flag));
+                    }
+                }
+                None => target = Some(value.clone()),
+            }
+        }
+
+        let mut opt_level = string("OPT_LEVEL");
+        if let Some((flag, value)) = last_codegen(&flags, "opt-level") {
+            if let Some(env_value) = &opt_level {
+                if env_value != value {
+                    conflicts.push(conflict("OPT_LEVEL", env_value, flag));
+                }
+            }
+            opt_level = Some(value.to_owned());
+        }
+
+        let mut debug = string("DEBUG").map(|value| value == "true");
+        if let Some((flag, value)) = last_codegen(&flags, "debuginfo") {
+            let enabled = !matches!(value, "0" | "none" | "n" | "no" | "off" | "false");
+            if let Some(env_value) = debug {
+                if env_value != enabled {
+                    conflicts.push(conflict("DEBUG", &env_value.to_string(), flag));
+                }
+            }
+            debug = Some(enabled);
+        }
+
+        let env_features: Vec<String> = string("CARGO_CFG_TARGET_FEATURE")
+            .map(|value| {
+                value
+                    .split(',')
+                    .filter(|feature| !feature.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut target_features = env_features.clone();
+        for flag in &flags {
+            let Flag::Codegen {
+                opt,
+                value: Some(value),
+            } = flag
+            else {
+                continue;
+            };
+            if option_name(opt) != "target-feature" {
+                continue;
+            }
+            let mut conflicting = false;
+            for feature in value.split(',') {
+                let (enabled, name) = if let Some(name) = feature.strip_prefix('+') {
+                    (true, name)
+                } else if let Some(name) = feature.strip_prefix('-') {
+                    (false, name)
+                } else {
+                    continue;
+                };
+                if env_features.iter().any(|present| present == name) != enabled {
+                    conflicting = true;
+                }
+                target_features.retain(|present| present != name);
+                if enabled {
+                    target_features.push(name.to_owned());
+                }
+            }
+            if conflicting && var("CARGO_CFG_TARGET_FEATURE").is_some() {
+                let env_value = env_features.join(",");
+                conflicts.push(conflict("CARGO_CFG_TARGET_FEATURE", &env_value, flag));
+            }
+        }
+        target_features.sort();
+
+        let mut panic = string("CARGO_CFG_PANIC");
+        if let Some((flag, value)) = last_codegen(&flags, "panic") {
+            if let Some(env_value) = &panic {
+                if env_value != value {
+                    conflicts.push(conflict("CARGO_CFG_PANIC", env_value, flag));
+                }
+            }
+            panic = Some(value.to_owned());
+        }
+
+        let mut linker = var("RUSTC_LINKER").map(PathBuf::from);
+        if let Some((flag, value)) = last_codegen(&flags, "linker") {
+            if let Some(env_value) = &linker {
+                if env_value != Path::new(value) {
+                    let env_value = env_value.to_string_lossy();
+                    conflicts.push(conflict("RUSTC_LINKER", &env_value, flag));
+                }
+            }
+            linker = Some(PathBuf::from(value));
+        }
+
+        BuildContext {
+            target,
+            host: string("HOST"),
+            profile: string("PROFILE"),
+            opt_level,
+            debug,
+            target_features,
+            panic,
+            rustc: var("RUSTC").map(PathBuf::from),
+            linker,
+            flags,
+            conflicts,
+        }
+    }
+}
+
+fn last<'a, T>(flags: &'a [Flag], f: impl Fn(&'a Flag) -> Option<T>) -> Option<T> {
+    flags.iter().rev().find_map(f)
+}
+
+fn last_codegen<'a>(flags: &'a [Flag], name: &str) -> Option<(&'a Flag, &'a str)> {
+    last(flags, |flag| match flag {
+        Flag::Codegen {
+            opt,
+            value: Some(value),
+        } if option_name(opt) == name => Some((flag, value.as_str())),
+        _ => None,
+    })
+}
+
+fn conflict(var: &str, value: &str, flag: &Flag) -> Conflict {
+    Conflict {
+        var: var.to_owned(),
+        value: value.to_owned(),
+        flag: flag.clone(),
+    }
+}
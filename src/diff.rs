@@ -0,0 +1,298 @@
+use crate::canonical::{is_list_option, normalize, override_key, split_option};
+use crate::lints::is_group;
+use crate::{Flag, RustFlags};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+/// Compare the effect of two sets of flags.
+///
+/// Flags are compared by the settings they produce rather than token by
+/// token:
+///
+/// - flags are first put in the same [canonical spelling][crate::canonicalize],
+///   so `-O` is the same as `-Copt-level=2`, and for codegen options, `-Z`
+///   options and flags like `--target` or `--edition` only the last
+///   occurrence counts;
+/// - `-Ctarget-feature` is compared by the resulting set of enabled and
+///   disabled features;
+/// - `-L`, `-l`, and options that accumulate like `-Clink-arg`,
+///   `-Cllvm-args` and `-Cpasses`, are compared as ordered lists, since
+///   their order can change the linked artifact;
+/// - other repeatable flags like `--cfg` and `--crate-type` are compared as
+///   sets, regardless of order or duplicates;
+/// - each lint's level is that of the last flag naming it, except that later
+///   flags do not override `--forbid` or `--force-warn`, and the order of
+///   an individual lint relative to a later lint group such as `warnings` or
+///   `clippy::pedantic` counts, because the group may override it.
+pub fn diff(old: &[Flag], new: &[Flag]) -> Diff {
+    let old = settings(old);
+    let new = settings(new);
+    let mut changes = Vec::new();
+    for (setting, old_value) in &old {
+        match new.get(setting) {
+            None => changes.push(Change::Removed {
+                setting: setting.clone(),
+                value: old_value.clone(),
+            }),
+            Some(new_value) if new_value != old_value => changes.push(Change::Changed {
+                setting: setting.clone(),
+                old: old_value.clone(),
+                new: new_value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (setting, new_value) in &new {
+        if !old.contains_key(setting) {
+            changes.push(Change::Added {
+                setting: setting.clone(),
+                value: new_value.clone(),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.setting().cmp(b.setting()));
+    Diff { changes }
+}
+
+impl RustFlags {
+    /// Compare the effect of these flags against `new`. See [`diff`].
+    pub fn diff(self, new: RustFlags) -> Diff {
+        diff(&self.collect::<Vec<Flag>>(), &new.collect::<Vec<Flag>>())
+    }
+}
+
+/// **Differences between the effective settings of two sets of flags**
+///
+/// Returned by [`diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    /// The settings that differ, in order of setting name.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether both sets of flags have the same effect.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(formatter, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// **One setting that differs**
+///
+/// A setting is named after the flag that controls it, like `-C opt-level`,
+/// or `lint dead_code` for the level of a lint. Settings like
+/// `--cfg feature="std"` which are either present or not have no value.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Change {
+    /// Set only by the new flags.
+    Added {
+        setting: String,
+        value: Option<String>,
+    },
+    /// Set only by the old flags.
+    Removed {
+        setting: String,
+        value: Option<String>,
+    },
+    /// Set to different values.
+    Changed {
+        setting: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl Change {
+    /// Name of the setting that differs.
+    pub fn setting(&self) -> &str {
+        match self {
+            Change::Added { setting, .. }
+            | Change::Removed { setting, .. }
+            | Change::Changed { setting, .. } => setting,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added { setting, value } => {
+                write!(formatter, "+ {}", setting)?;
+                if let Some(value) = value {
+                    write!(formatter, ": {}", value)?;
+                }
+                Ok(())
+            }
+            Change::Removed { setting, value } => {
+                write!(formatter, "- {}", setting)?;
+                if let Some(value) = value {
+                    write!(formatter, ": {}", value)?;
+                }
+                Ok(())
+            }
+            Change::Changed { setting, old, new } => write!(
+                formatter,
+                "~ {}: {} -> {}",
+                setting,
+                old.as_deref().unwrap_or("(unset)"),
+                new.as_deref().unwrap_or("(unset)"),
+            ),
+        }
+    }
+}
+
+fn settings(flags: &[Flag]) -> BTreeMap<String, Option<String>> {
+    let mut settings = BTreeMap::new();
+    let mut target_features: Vec<(String, char)> = Vec::new();
+    let mut lints: Vec<(String, &str)> = Vec::new();
+    let mut search_paths: Vec<String> = Vec::new();
+    let mut libraries: Vec<String> = Vec::new();
+
+    for flag in flags.iter().map(normalize) {
+        match &flag {
+            Flag::Codegen { opt, value } if opt == "target-feature" => {
+                for feature in value.as_deref().unwrap_or_default().split(',') {
+                    let (sign, name) = match feature.chars().next() {
+                        Some(sign @ ('+' | '-')) => (sign, &feature[1..]),
+                        _ => ('+', feature),
+                    };
+                    if !name.is_empty() {
+                        target_features.retain(|(existing, _)| existing != name);
+                        target_features.push((name.to_owned(), sign));
+                    }
+                }
+            }
+            Flag::Codegen { opt, value } if is_list_option(opt) => {
+                push_list(&mut settings, "-C", opt, value.as_deref());
+            }
+            Flag::Z(option) if is_list_option(split_option(option).0) => {
+                let (opt, value) = split_option(option);
+                push_list(&mut settings, "-Z", opt, value);
+            }
+            Flag::LibrarySearchPath { .. } | Flag::Link { .. } => {
+                let Some(value) = flag.clone().into_iter().nth(1) else {
+                    continue;
+                };
+                let value = value.to_string_lossy().into_owned();
+                if let Flag::Link { .. } = flag {
+                    libraries.push(value);
+                } else if !search_paths.contains(&value) {
+                    search_paths.push(value);
+                }
+            }
+            Flag::Allow(name) => lints.push((name.clone(), "--allow")),
+            Flag::Warn(name) => lints.push((name.clone(), "--warn")),
+            Flag::ForceWarn(name) => lints.push((name.clone(), "--force-warn")),
+            Flag::Deny(name) => lints.push((name.clone(), "--deny")),
+            Flag::Forbid(name) => lints.push((name.clone(), "--forbid")),
+            _ => {
+                let args: Vec<String> = flag
+                    .clone()
+                    .into_iter()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .collect();
+                if let Some(key) = override_key(&flag) {
+                    let value = match &flag {
+                        Flag::Codegen { value, .. } => value.clone(),
+                        Flag::Z(option) => split_option(option).1.map(str::to_owned),
+                        _ => args.get(1).cloned(),
+                    };
+                    settings.insert(key, value);
+                } else {
+                    settings.insert(args.join(" "), None);
+                }
+            }
+        }
+    }
+
+    // Link order decides which symbols static libraries provide, and search
+    // path order decides which library file is found.
+    if !search_paths.is_empty() {
+        settings.insert("-L".to_owned(), Some(search_paths.join(" ")));
+    }
+    if !libraries.is_empty() {
+        settings.insert("-l".to_owned(), Some(libraries.join(" ")));
+    }
+
+    if !target_features.is_empty() {
+        target_features.sort();
+        let features: Vec<String> = target_features
+            .iter()
+            .map(|(name, sign)| format!("{}{}", sign, name))
+            .collect();
+        settings.insert("-C target-feature".to_owned(), Some(features.join(",")));
+    }
+
+    // Last occurrence of each lint wins, unless an earlier one forbids or
+    // force-warns it. An individual lint followed by a group may be
+    // overridden by that group, so the later groups are part of its effective
+    // setting.
+    let sticky = |level: &str| level == "--forbid" || level == "--force-warn";
+    let mut deduped: Vec<(String, &str)> = Vec::new();
+    for (name, level) in lints {
+        if !sticky(level)
+            && deduped
+                .iter()
+                .any(|(existing, level)| *existing == name && sticky(level))
+        {
+            continue;
+        }
+        deduped.retain(|(existing, _)| *existing != name);
+        deduped.push((name, level));
+    }
+    for (i, (name, level)) in deduped.iter().enumerate() {
+        let mut value = (*level).to_owned();
+        if !is_group(name) {
+            let later_groups: Vec<String> = deduped[i + 1..]
+                .iter()
+                .filter(|(name, _)| is_group(name))
+                .map(|(name, level)| format!("{} {}", level, name))
+                .collect();
+            if !later_groups.is_empty() {
+                value = format!("{}, then {}", value, later_groups.join(", "));
+            }
+        }
+        settings.insert(format!("lint {}", name), Some(value));
+    }
+
+    settings
+}
+
+// Append the arguments of an option that accumulates across occurrences.
+fn push_list(
+    settings: &mut BTreeMap<String, Option<String>>,
+    prefix: &str,
+    opt: &str,
+    value: Option<&str>,
+) {
+    let list = settings
+        .entry(format!("{} {}", prefix, opt))
+        .or_default()
+        .get_or_insert_with(String::new);
+    let value = value.unwrap_or_default();
+    let args: Vec<&str> = if opt.ends_with("args") || opt == "passes" {
+        value.split_whitespace().collect()
+    } else {
+        vec![value]
+    };
+    for arg in args {
+        if !list.is_empty() {
+            list.push(' ');
+        }
+        list.push_str(arg);
+    }
+}
@@ -0,0 +1,51 @@
+use std::env;
+use std::ffi::OsString;
+
+/// **Where environment variables are read from**
+///
+/// Every function in this crate that reads environment variables has a
+/// variant taking an `EnvSource`, so that build scripts and tools can be
+/// tested with fixed inputs instead of mutating the environment of the test
+/// process, which is shared by all test threads.
+///
+/// Any closure `Fn(&str) -> Option<OsString>` is an `EnvSource`.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::ffi::OsString;
+///
+/// let vars = HashMap::from([("CARGO_ENCODED_RUSTFLAGS", "-Dwarnings")]);
+/// let flags = rustflags::from_env_with(|key: &str| vars.get(key).map(OsString::from));
+/// assert_eq!(flags.count(), 1);
+/// ```
+pub trait EnvSource {
+    /// Value of the environment variable `key`, or None if it is not set.
+    fn var_os(&self, key: &str) -> Option<OsString>;
+
+    /// Value of the environment variable `key` if it is set and valid
+    /// Unicode.
+    fn var(&self, key: &str) -> Option<String> {
+        self.var_os(key).and_then(|value| value.into_string().ok())
+    }
+}
+
+impl<F> EnvSource for F
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self(key)
+    }
+}
+
+/// **The environment of the current process**
+///
+/// This is the `EnvSource` used by functions that do not take one explicitly.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        env::var_os(key)
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt::{self, Debug, Display};
+use std::io;
+use std::path::PathBuf;
+
+/// Error produced by the conversions in this crate.
+pub struct Error {
+    kind: ErrorKind,
+}
+
+pub(crate) enum ErrorKind {
+    NoDirective(String),
+    NotUtf8(String),
+    Io { path: PathBuf, error: io::Error },
+    Config { path: PathBuf, message: String },
+    Env { name: String, message: String },
+    Manifest(String),
+    Policy(String),
+    NoRustc,
+    Argfile { path: PathBuf, message: String },
+    Exec { path: PathBuf, error: io::Error },
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind) -> Self {
+        Error { kind }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::NoDirective(flag) => {
+                write!(formatter, "`{}` has no build script equivalent", flag)
+            }
+            ErrorKind::NotUtf8(flag) => {
+                write!(formatter, "`{}` is not valid UTF-8", flag)
+            }
+            ErrorKind::Io { path, error } => {
+                write!(formatter, "failed to read {}: {}", path.display(), error)
+            }
+            ErrorKind::Config { path, message } => {
+                write!(
+                    formatter,
+                    "invalid config in {}: {}",
+                    path.display(),
+                    message
+                )
+            }
+            ErrorKind::Env { name, message } => {
+                write!(formatter, "invalid value in {}: {}", name, message)
+            }
+            ErrorKind::Manifest(message) => write!(formatter, "invalid manifest: {}", message),
+            ErrorKind::Policy(message) => write!(formatter, "invalid policy: {}", message),
+            ErrorKind::NoRustc => formatter.write_str("missing path of rustc in wrapper arguments"),
+            ErrorKind::Argfile { path, message } => {
+                write!(
+                    formatter,
+                    "invalid argument file {}: {}",
+                    path.display(),
+                    message
+                )
+            }
+            ErrorKind::Exec { path, error } => {
+                write!(formatter, "failed to run {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Error({:?})", self.to_string())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io { error, .. } | ErrorKind::Exec { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::{string, wrapper, Flag};
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Pass the rustflags of the current build on to a nested Cargo invocation.
+///
+/// The flags from `CARGO_ENCODED_RUSTFLAGS` are handed to `edit`, which may
+/// remove, rewrite or add flags before they are set on `cmd`. See
+/// [`set_cargo_rustflags`] for how they are set.
+///
+/// Flags that `edit` leaves alone are passed on exactly as they were given.
+/// Arguments that this crate does not recognize as flags are not seen by
+/// `edit`, and are passed on too.
+///
+/// ```no_run
+/// use rustflags::Flag;
+/// use std::process::Command;
+///
+/// let mut cargo = Command::new(std::env::var_os("CARGO").unwrap());
+/// cargo.args(["build", "--manifest-path", "firmware/Cargo.toml"]);
+/// rustflags::forward_rustflags(&mut cargo, |flags| {
+///     flags.retain(|flag| !matches!(flag, Flag::Target(_)));
+///     flags.push(Flag::Cfg {
+///         name: "nested".to_owned(),
+///         value: None,
+///     });
+/// });
+/// ```
+pub fn forward_rustflags(cmd: &mut Command, edit: impl FnOnce(&mut Vec<Flag>)) -> &mut Command {
+    forward_rustflags_with(cmd, ProcessEnv, edit)
+}
+
+/// Pass the rustflags from `CARGO_ENCODED_RUSTFLAGS` in `env` on to a nested
+/// Cargo invocation, like [`forward_rustflags`].
+#[allow(clippy::needless_pass_by_value)]
+pub fn forward_rustflags_with(
+    cmd: &mut Command,
+    env: impl EnvSource,
+    edit: impl FnOnce(&mut Vec<Flag>),
+) -> &mut Command {
+    let encoded = env.var_os("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+    let args = wrapper::group(&string::split_encoded(&encoded));
+    let mut flags: Vec<Flag> = args.iter().flat_map(|arg| arg.flags.clone()).collect();
+    edit(&mut flags);
+
+    // Keep the original tokens of each flag that is still present, in order.
+    // Anything else in the edited flags is new or rewritten, and gets rendered
+    // in place.
+    let mut tokens = Vec::new();
+    let mut rest = flags.as_slice();
+    for arg in &args {
+        if arg.flags.is_empty() {
+            tokens.extend(arg.tokens.iter().cloned());
+            continue;
+        }
+        let Some(i) = rest
+            .windows(arg.flags.len())
+            .position(|window| window == arg.flags)
+        else {
+            continue;
+        };
+        tokens.extend(rest[..i].iter().cloned().flatten());
+        tokens.extend(arg.tokens.iter().cloned());
+        rest = &rest[i + arg.flags.len()..];
+    }
+    tokens.extend(rest.iter().cloned().flatten());
+
+    cmd.env("CARGO_ENCODED_RUSTFLAGS", tokens.join(OsStr::new("\x1F")))
+        .env_remove("RUSTFLAGS")
+}
+
+/// Set the rustflags of a Cargo invocation.
+///
+/// The flags are encoded into `CARGO_ENCODED_RUSTFLAGS`, and `RUSTFLAGS` is
+/// removed so that the two cannot disagree. Cargo gives the environment
+/// precedence over rustflags in config files, so this replaces any rustflags
+/// the nested project configures for itself, even if `flags` is empty.
+pub fn set_cargo_rustflags<I>(cmd: &mut Command, flags: I) -> &mut Command
+where
+    I: IntoIterator<Item = Flag>,
+{
+    cmd.env("CARGO_ENCODED_RUSTFLAGS", crate::to_encoded(flags))
+        .env_remove("RUSTFLAGS")
+}
@@ -0,0 +1,110 @@
+use crate::cfg;
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::Flag;
+use std::path::Path;
+
+/// **Whether rustflags reach host artifacts such as build scripts and proc
+/// macros**
+///
+/// Cargo passes the same rustflags to host artifacts as to target artifacts
+/// only when it is invoked without `--target` (and without `build.target`).
+/// In a build with an explicit target, host artifacts are compiled with
+/// `host.rustflags` instead, which build scripts do not get to see.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct HostInfo {
+    /// Triple of the machine running the build, from `HOST`.
+    pub host: String,
+
+    /// Triple being compiled for, from `TARGET`.
+    pub target: String,
+
+    /// Whether the target differs from the host.
+    pub cross: bool,
+
+    /// Whether Cargo was given an explicit `--target`, even if it is the same
+    /// as the host.
+    pub explicit_target: bool,
+
+    /// Whether rustflags of the target apply to host artifacts as well.
+    pub applies_to_host: bool,
+}
+
+impl HostInfo {
+    /// Classify the current build from within a build script.
+    ///
+    /// The build is a cross build if `TARGET` differs from `HOST`. If Cargo
+    /// does not provide `TARGET`, the `CARGO_CFG_TARGET_ARCH`,
+    /// `CARGO_CFG_TARGET_OS` and `CARGO_CFG_TARGET_ENV` values are compared
+    /// against the host triple instead.
+    ///
+    /// Cargo does not tell build scripts whether `--target` was passed. When
+    /// host and target are the same, this is inferred from `OUT_DIR`, which
+    /// contains a directory named after the target triple only in builds with
+    /// an explicit target.
+    ///
+    /// Rustflags are considered to apply to host artifacts exactly when there
+    /// is no explicit target, unless the unstable `target-applies-to-host`
+    /// config is turned off through `CARGO_TARGET_APPLIES_TO_HOST=false`.
+    pub fn from_env() -> Self {
+        HostInfo::from_env_with(ProcessEnv)
+    }
+
+    /// Classify the build described by `env`, in the same way as
+    /// [`HostInfo::from_env`].
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_env_with(env: impl EnvSource) -> Self {
+        let host = env.var("HOST").unwrap_or_default();
+        let target = env.var("TARGET").unwrap_or_default();
+
+        let cross = if target.is_empty() {
+            let cfgs = cfg::target_cfgs(&host);
+            ["target_arch", "target_os", "target_env"]
+                .iter()
+                .any(|name| match env.var(&cfg_var(name)) {
+                    Some(value) => !cfgs.contains(&((*name).to_owned(), Some(value))),
+                    None => false,
+                })
+        } else {
+            target != host
+        };
+
+        let explicit_target = cross
+            || env.var_os("OUT_DIR").is_some_and(|out_dir| {
+                !target.is_empty()
+                    && Path::new(&out_dir)
+                        .components()
+                        .any(|component| component.as_os_str() == target.as_str())
+            });
+
+        let applies_to_host = !explicit_target
+            && env
+                .var_os("CARGO_TARGET_APPLIES_TO_HOST")
+                .map_or(true, |value| value != "false");
+
+        HostInfo {
+            host,
+            target,
+            cross,
+            explicit_target,
+            applies_to_host,
+        }
+    }
+
+    /// The subset of `flags` that also affects compilation of build scripts
+    /// and proc macros.
+    ///
+    /// For example, a `-Zsanitizer=address` in this subset means the build
+    /// script's own proc-macro dependencies are instrumented too.
+    pub fn host_flags<'a>(&self, flags: &'a [Flag]) -> Vec<&'a Flag> {
+        if self.applies_to_host {
+            flags.iter().collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn cfg_var(name: &str) -> String {
+    format!("CARGO_CFG_{}", name.to_uppercase())
+}
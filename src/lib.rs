@@ -56,6 +56,18 @@
 //!     builder.build();
 //! }
 //! ```
+//!
+//! # Command-line tool
+//!
+//! With the `cli` feature enabled, this crate also provides a `rustflags`
+//! binary for inspecting flags from the command line:
+//!
+//! ```console
+//! $ cargo install rustflags --features cli
+//! $ RUSTFLAGS='-Ctarget-cpu=native --cfg tokio_unstable' rustflags
+//! -C target-cpu=native
+//! --cfg tokio_unstable
+//! ```
 
 #![doc(html_root_url = "https://docs.rs/rustflags/0.1.7")]
 #![allow(
@@ -73,25 +85,79 @@
     clippy::unnecessary_wraps
 )]
 
+mod argfile;
+mod build_script;
+mod canonical;
+mod cfg;
+mod check;
+mod codegen;
+mod config;
+mod context;
+mod diff;
+mod env_source;
+mod error;
+mod forward;
+mod host;
+mod linker;
+mod lints;
+mod native;
 mod parse;
+mod pkgconfig;
+mod policy;
+mod probe;
 mod render;
+mod rustdoc;
 mod string;
+mod toml;
+mod wrapper;
 mod write;
 
-use crate::string::{EnvStr, EnvString};
-use std::env;
+pub use crate::argfile::expand_argfiles;
+pub use crate::build_script::{
+    from_build_script_output, from_build_script_output_file, to_build_script_output,
+};
+pub use crate::canonical::{canonicalize, fingerprint};
+pub use crate::check::{check, Channel, Diagnostic, Severity};
+pub use crate::codegen::{llvm_args, passes};
+pub use crate::config::{
+    flag_sources, flag_sources_with, from_cargo_config, from_cargo_config_with, FlagReport,
+    FlagSource, SourcedFlags,
+};
+pub use crate::context::{BuildContext, Conflict};
+pub use crate::diff::{diff, Change, Diff};
+pub use crate::env_source::{EnvSource, ProcessEnv};
+pub use crate::error::Error;
+pub use crate::forward::{forward_rustflags, forward_rustflags_with, set_cargo_rustflags};
+pub use crate::host::HostInfo;
+pub use crate::linker::{LinkerConfig, LinkerFlavor, SelfContained};
+pub use crate::lints::{from_cargo_lints, to_cargo_lints};
+pub use crate::native::{
+    c_linker_args, c_linker_args_for_staticlib, find_native_libraries, NativeLibrary,
+};
+pub use crate::pkgconfig::{from_pkg_config, to_pkg_config_libs};
+pub use crate::policy::Policy;
+pub use crate::probe::ProbeCommand;
+pub use crate::rustdoc::{
+    from_encoded_rustdoc, from_env_rustdoc, from_env_rustdoc_with, RustdocFlag, RustdocFlags,
+};
+pub use crate::wrapper::RustcInvocation;
+
+use crate::parse::Parser;
 use std::ffi::{OsStr, OsString};
 use std::fmt::{self, Display, Write};
 use std::path::PathBuf;
 
 /// Parse flags from CARGO_ENCODED_RUSTFLAGS environment variable.
 pub fn from_env() -> RustFlags {
-    let encoded = env::var_os("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+    from_env_with(ProcessEnv)
+}
+
+/// Parse flags from CARGO_ENCODED_RUSTFLAGS as provided by `env`.
+#[allow(clippy::needless_pass_by_value)]
+pub fn from_env_with(env: impl EnvSource) -> RustFlags {
+    let encoded = env.var_os("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
     RustFlags {
-        encoded: EnvString::new(encoded),
-        pos: 0,
-        repeat: None,
-        short: false,
+        parser: Parser::new(encoded),
     }
 }
 
@@ -100,34 +166,48 @@ pub fn from_env() -> RustFlags {
 /// This is a valid format for the following environment variables:
 ///
 /// - `CARGO_ENCODED_RUSTFLAGS` (Cargo 1.55+)
-/// - `CARGO_ENCODED_RUSTDOCFLAGS` (Cargo 1.55+)
+/// - `CARGO_ENCODED_RUSTDOCFLAGS` (Cargo 1.55+), although rustdoc-specific
+///   flags are only recognized by [`from_encoded_rustdoc`]
 pub fn from_encoded(encoded: &OsStr) -> RustFlags {
     RustFlags {
-        encoded: EnvString::new(encoded.to_owned()),
-        pos: 0,
-        repeat: None,
-        short: false,
+        parser: Parser::new(encoded.to_owned()),
     }
 }
 
+/// Render flags into a string separated with ASCII unit separator ('\x1f').
+///
+/// This is the inverse of [`from_encoded`] and produces a value suitable for
+/// `CARGO_ENCODED_RUSTFLAGS`.
+pub fn to_encoded<I>(flags: I) -> OsString
+where
+    I: IntoIterator,
+    I::Item: IntoIterator<Item = OsString>,
+{
+    let mut encoded = OsString::new();
+    for (i, arg) in flags.into_iter().flatten().enumerate() {
+        if i > 0 {
+            encoded.push("\x1F");
+        }
+        encoded.push(arg);
+    }
+    encoded
+}
+
 /// **Iterator of rustc flags**
 pub struct RustFlags {
-    encoded: EnvString,
-    pos: usize,
-    repeat: Option<(fn(&EnvStr) -> Option<(Flag, usize)>, usize)>,
-    short: bool,
+    parser: Parser<Flag>,
 }
 
 impl Iterator for RustFlags {
     type Item = Flag;
 
     fn next(&mut self) -> Option<Self::Item> {
-        parse::parse(self)
+        parse::parse(&mut self.parser)
     }
 }
 
 /// **One flag recognized by rustc**
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum Flag {
     /// `-h`, `--help`
@@ -140,6 +220,11 @@ pub enum Flag {
     /// Configure the compilation environment.
     Cfg { name: String, value: Option<String> },
 
+    /// `--check-cfg SPEC`
+    ///
+    /// Provide list of expected cfgs for checking.
+    CheckCfg(String),
+
     /// `-L [KIND=]PATH`
     ///
     /// Add a directory to the library search path.
@@ -527,3 +612,26 @@ impl Display for Color {
         })
     }
 }
+
+/// **Aspect of compilation affected by a flag**
+///
+/// Returned by [`Flag::categories`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Category {
+    /// Affects the generated code or the ABI, like `-Copt-level` or
+    /// `--target`.
+    Codegen,
+    /// Only affects warnings and errors and how they are reported, like
+    /// `--deny` or `--error-format`.
+    Diagnostics,
+    /// Affects where output files are placed or what they are named, like
+    /// `--out-dir` or `--emit`.
+    Output,
+    /// Affects linking, like `-L`, `-l` or `-Clinker`.
+    Linking,
+    /// Affects conditional compilation, like `--cfg` or `-Ctarget-feature`.
+    Cfg,
+    /// Prints information instead of compiling, like `--print` or `--help`.
+    Informational,
+}
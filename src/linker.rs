@@ -0,0 +1,265 @@
+use crate::parse::option_name;
+use crate::{Flag, LinkModifierPrefix};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+
+/// **Linker that rustc will invoke, as configured by codegen options**
+///
+/// Derived from `-Clinker`, `-Clinker-flavor`, `-Clink-self-contained` and
+/// `-Zlinker-features`, falling back to the target's defaults for anything not
+/// specified explicitly.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LinkerConfig {
+    /// Path given by `-Clinker`, if any.
+    pub linker: Option<PathBuf>,
+
+    /// Flavor of the linker, i.e. which command line syntax it accepts.
+    pub flavor: LinkerFlavor,
+
+    /// Whether `flavor` came from `-Clinker-flavor`, as opposed to being
+    /// inferred from the linker's name or the target.
+    pub explicit_flavor: bool,
+
+    /// Argument of `-Clink-self-contained`.
+    pub self_contained: SelfContained,
+
+    /// Components enabled or disabled by `-Zlinker-features`.
+    pub linker_features: Vec<(LinkModifierPrefix, String)>,
+
+    /// Values of every `-Clink-arg`, `-Clink-args` and `-Cpre-link-arg`, in
+    /// the order they appeared.
+    pub link_args: Vec<String>,
+}
+
+/// Argument of `-Clinker-flavor`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum LinkerFlavor {
+    /// `gcc`
+    Gcc,
+    /// `ld`
+    Ld,
+    /// `msvc`
+    Msvc,
+    /// `em`
+    Em,
+    /// `wasm-ld`
+    WasmLd,
+    /// `ld.lld`
+    LdLld,
+    /// `ld64.lld`
+    Ld64Lld,
+    /// `lld-link`
+    LldLink,
+    /// `ptx-linker`
+    PtxLinker,
+    /// `bpf-linker`
+    BpfLinker,
+}
+
+impl Display for LinkerFlavor {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            LinkerFlavor::Gcc => "gcc",
+            LinkerFlavor::Ld => "ld",
+            LinkerFlavor::Msvc => "msvc",
+            LinkerFlavor::Em => "em",
+            LinkerFlavor::WasmLd => "wasm-ld",
+            LinkerFlavor::LdLld => "ld.lld",
+            LinkerFlavor::Ld64Lld => "ld64.lld",
+            LinkerFlavor::LldLink => "lld-link",
+            LinkerFlavor::PtxLinker => "ptx-linker",
+            LinkerFlavor::BpfLinker => "bpf-linker",
+        })
+    }
+}
+
+/// Argument of `-Clink-self-contained`
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum SelfContained {
+    /// Not specified; rustc decides based on the target (the default).
+    #[default]
+    Inferred,
+    /// `yes`, `y`, `on`, `true`
+    Yes,
+    /// `no`, `n`, `off`, `false`
+    No,
+    /// Comma separated components, each with a prefix of either '+' to
+    /// enable or '-' to disable, such as `+linker,-crto`.
+    Components(Vec<(LinkModifierPrefix, String)>),
+}
+
+impl LinkerConfig {
+    /// Determine the linker configuration implied by `flags` when compiling
+    /// for `target`.
+    pub fn new(flags: &[Flag], target: &str) -> Self {
+        let mut linker = None;
+        let mut explicit_flavor = None;
+        let mut self_contained = SelfContained::Inferred;
+        let mut linker_features = Vec::new();
+        let mut link_args = Vec::new();
+
+        for flag in flags {
+            match flag {
+                Flag::Codegen { opt, value } => match (option_name(opt).as_str(), value) {
+                    ("linker", Some(value)) => linker = Some(PathBuf::from(value)),
+                    ("linker-flavor", Some(value)) => {
+                        if let Some(flavor) = parse_flavor(value) {
+                            explicit_flavor = Some(flavor);
+                        }
+                    }
+                    ("link-self-contained", Some(value)) => {
+                        self_contained = parse_self_contained(value);
+                    }
+                    ("link-arg" | "pre-link-arg", Some(value)) => {
+                        link_args.push(value.clone());
+                    }
+                    ("link-args" | "pre-link-args", Some(value)) => {
+                        link_args.extend(value.split_whitespace().map(str::to_owned));
+                    }
+                    _ => {}
+                },
+                Flag::Z(z) => {
+                    let Some((opt, value)) = z.split_once('=') else {
+                        continue;
+                    };
+                    match option_name(opt).as_str() {
+                        "linker-features" => linker_features.extend(parse_components(value)),
+                        "pre-link-arg" => link_args.push(value.to_owned()),
+                        "pre-link-args" => {
+                            link_args.extend(value.split_whitespace().map(str::to_owned));
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let flavor = explicit_flavor
+            .or_else(|| linker.as_deref().and_then(infer_from_linker))
+            .unwrap_or_else(|| infer_from_target(target));
+
+        LinkerConfig {
+            linker,
+            flavor,
+            explicit_flavor: explicit_flavor.is_some(),
+            self_contained,
+            linker_features,
+            link_args,
+        }
+    }
+
+    /// Whether the linker that will run is some flavor of LLD, either
+    /// directly or through a C compiler driver.
+    pub fn uses_lld(&self) -> bool {
+        match self.flavor {
+            LinkerFlavor::WasmLd
+            | LinkerFlavor::LdLld
+            | LinkerFlavor::Ld64Lld
+            | LinkerFlavor::LldLink => return true,
+            _ => {}
+        }
+        if let Some(name) = self.linker.as_deref().and_then(file_name) {
+            if name.contains("lld") {
+                return true;
+            }
+        }
+        let mut lld = false;
+        for (prefix, feature) in &self.linker_features {
+            if feature == "lld" {
+                lld = *prefix == LinkModifierPrefix::Enable;
+            }
+        }
+        lld || self
+            .link_args
+            .iter()
+            .any(|arg| arg == "-fuse-ld=lld" || arg == "-Wl,-fuse-ld=lld")
+    }
+}
+
+fn parse_flavor(value: &str) -> Option<LinkerFlavor> {
+    Some(match value {
+        "gcc" => LinkerFlavor::Gcc,
+        "ld" => LinkerFlavor::Ld,
+        "msvc" => LinkerFlavor::Msvc,
+        "em" => LinkerFlavor::Em,
+        "wasm-ld" => LinkerFlavor::WasmLd,
+        "ld.lld" => LinkerFlavor::LdLld,
+        "ld64.lld" => LinkerFlavor::Ld64Lld,
+        "lld-link" => LinkerFlavor::LldLink,
+        "ptx-linker" => LinkerFlavor::PtxLinker,
+        "bpf-linker" => LinkerFlavor::BpfLinker,
+        _ => return None,
+    })
+}
+
+fn parse_self_contained(value: &str) -> SelfContained {
+    match value {
+        "y" | "yes" | "on" | "true" => SelfContained::Yes,
+        "n" | "no" | "off" | "false" => SelfContained::No,
+        _ => SelfContained::Components(parse_components(value)),
+    }
+}
+
+fn parse_components(value: &str) -> Vec<(LinkModifierPrefix, String)> {
+    let mut components = Vec::new();
+    for component in value.split(',') {
+        let prefix = match component.chars().next() {
+            Some('+') => LinkModifierPrefix::Enable,
+            Some('-') => LinkModifierPrefix::Disable,
+            _ => continue,
+        };
+        components.push((prefix, component[1..].to_owned()));
+    }
+    components
+}
+
+// File name without an `.exe` extension. Other extensions are part of the
+// name, as in `ld.lld`.
+fn file_name(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    Some(name.strip_suffix(".exe").unwrap_or(name))
+}
+
+// Mirrors rustc's inference of a flavor from the linker's file name.
+fn infer_from_linker(linker: &Path) -> Option<LinkerFlavor> {
+    let name = file_name(linker)?;
+    Some(match name {
+        "emcc" => LinkerFlavor::Em,
+        "wasm-ld" => LinkerFlavor::WasmLd,
+        "ld.lld" => LinkerFlavor::LdLld,
+        "ld64.lld" => LinkerFlavor::Ld64Lld,
+        "lld-link" => LinkerFlavor::LldLink,
+        "link" => LinkerFlavor::Msvc,
+        "gcc" | "g++" | "clang" | "clang++" | "cc" | "c++" => LinkerFlavor::Gcc,
+        "ld" => LinkerFlavor::Ld,
+        _ if name.ends_with("-wasm-ld") => LinkerFlavor::WasmLd,
+        _ if name.ends_with("-gcc")
+            || name.ends_with("-g++")
+            || name.ends_with("-clang")
+            || name.ends_with("-clang++") =>
+        {
+            LinkerFlavor::Gcc
+        }
+        _ if name.ends_with("-ld") => LinkerFlavor::Ld,
+        _ => return None,
+    })
+}
+
+fn infer_from_target(target: &str) -> LinkerFlavor {
+    if target.ends_with("-msvc") {
+        LinkerFlavor::Msvc
+    } else if target.ends_with("-emscripten") {
+        LinkerFlavor::Em
+    } else if target.starts_with("wasm") {
+        LinkerFlavor::WasmLd
+    } else if target.starts_with("nvptx") {
+        LinkerFlavor::PtxLinker
+    } else if target.starts_with("bpf") {
+        LinkerFlavor::BpfLinker
+    } else {
+        LinkerFlavor::Gcc
+    }
+}
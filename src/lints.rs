@@ -0,0 +1,322 @@
+use crate::error::{Error, ErrorKind};
+use crate::toml::{self, Table, Value};
+use crate::Flag;
+use std::fmt::Write;
+
+/// Convert the `[lints]` table of a Cargo.toml into the lint flags Cargo
+/// passes to rustc.
+///
+/// The input is the contents of a manifest; its `[lints]` table is used, or
+/// `[workspace.lints]` if there is none. Flags come out in the order Cargo
+/// passes them: sorted by `priority` first, then in reverse order of the
+/// lint name without its tool prefix. Lints in `[lints.cargo]` are for Cargo
+/// itself and are skipped. Check-cfg entries from
+/// `unexpected_cfgs = { check-cfg = [...] }` follow as [`Flag::CheckCfg`].
+///
+/// A package with `lints.workspace = true` inherits its lints from the
+/// workspace manifest, which needs to be passed instead.
+///
+/// # Errors
+///
+/// Returns an error if the manifest is not valid TOML, its `[lints]` table is
+/// malformed or names an unknown lint level, or it has
+/// `lints.workspace = true`.
+pub fn from_cargo_lints(manifest: &str) -> Result<Vec<Flag>, Error> {
+    let manifest = toml::parse(manifest).map_err(|error| invalid(error.to_string()))?;
+    let lints = match manifest.get("lints") {
+        Some(lints) => lints,
+        None => match crate::config::get(&manifest, &["workspace", "lints"]) {
+            Some(lints) => lints,
+            None => return Ok(Vec::new()),
+        },
+    };
+    let lints = lints
+        .as_table()
+        .ok_or_else(|| invalid("expected `lints` to be a table".to_owned()))?;
+    if lints.contains_key("workspace") {
+        return Err(invalid(
+            "lints are inherited from the workspace manifest".to_owned(),
+        ));
+    }
+
+    let mut sorted = Vec::new();
+    let mut check_cfg = Vec::new();
+    for (tool, table) in lints {
+        if tool == "cargo" {
+            continue;
+        }
+        let table = table
+            .as_table()
+            .ok_or_else(|| invalid(format!("expected `lints.{}` to be a table", tool)))?;
+        for (name, config) in table {
+            let (level, priority) = lint_config(tool, name, config)?;
+            let qualified = if tool == "rust" {
+                name.clone()
+            } else {
+                format!("{}::{}", tool, name)
+            };
+            if qualified == "unexpected_cfgs" {
+                if let Some(value) = config.as_table().and_then(|config| config.get("check-cfg")) {
+                    check_cfg.extend(check_cfg_list(value)?);
+                }
+            }
+            sorted.push((priority, name, level, qualified));
+        }
+    }
+    // Same as Cargo, which sorts by priority and reverse name without the
+    // tool, then by the rendered flag.
+    sorted.sort_by(|a, b| {
+        let (priority_a, name_a, level_a, qualified_a) = a;
+        let (priority_b, name_b, level_b, qualified_b) = b;
+        priority_a
+            .cmp(priority_b)
+            .then_with(|| name_b.cmp(name_a))
+            .then_with(|| level_a.cmp(level_b))
+            .then_with(|| qualified_a.cmp(qualified_b))
+    });
+
+    let mut flags = Vec::new();
+    for (_priority, _name, level, name) in sorted {
+        flags.push(match level {
+            "forbid" => Flag::Forbid(name),
+            "deny" => Flag::Deny(name),
+            "warn" => Flag::Warn(name),
+            _ => Flag::Allow(name),
+        });
+    }
+    flags.extend(check_cfg.into_iter().map(Flag::CheckCfg));
+    Ok(flags)
+}
+
+/// Convert lint flags into a `[lints]` table for Cargo.toml.
+///
+/// This is the reverse of [`from_cargo_lints`], for moving `-D`, `-W`, `-A`
+/// and `-F` flags out of RUSTFLAGS or `.cargo/config.toml` into the manifest.
+/// Lints prefixed with a tool name, like `clippy::pedantic`, go into that
+/// tool's table. `--check-cfg` flags become `check-cfg` entries of
+/// `unexpected_cfgs`.
+///
+/// On the command line a later flag overrides earlier ones, except that a
+/// lint stays forbidden once `-F` is given, whereas Cargo orders lints by
+/// priority. When a lint group such as `clippy::pedantic`
+/// needs to take effect before individual lints, it is given a negative
+/// `priority` to preserve the meaning of the original flags. Other flags,
+/// including `--force-warn` and `--cap-lints` which have no manifest
+/// equivalent, are left out and need to stay in rustflags.
+pub fn to_cargo_lints(flags: &[Flag]) -> String {
+    let mut levels: Vec<(String, &str, bool)> = Vec::new();
+    let mut check_cfg = Vec::new();
+    for flag in flags {
+        let (level, name) = match flag {
+            Flag::Allow(name) => ("allow", name),
+            Flag::Warn(name) => ("warn", name),
+            Flag::Deny(name) => ("deny", name),
+            Flag::Forbid(name) => ("forbid", name),
+            Flag::CheckCfg(spec) => {
+                check_cfg.push(spec.as_str());
+                continue;
+            }
+            _ => continue,
+        };
+        let name = name.replace('-', "_");
+        // Rustc does not let later flags override `-F`.
+        if level != "forbid"
+            && levels
+                .iter()
+                .any(|(existing, level, _)| *existing == name && *level == "forbid")
+        {
+            continue;
+        }
+        levels.retain(|(existing, _, _)| *existing != name);
+        let group = is_group(&name);
+        levels.push((name, level, group));
+    }
+    if !check_cfg.is_empty() && !levels.iter().any(|(name, _, _)| name == "unexpected_cfgs") {
+        levels.push(("unexpected_cfgs".to_owned(), "warn", false));
+    }
+
+    // Each run of consecutive groups or individual lints forms one priority
+    // level, with the last run at Cargo's default priority of 0.
+    let mut runs = 0;
+    let mut priorities = Vec::new();
+    for (i, (_, _, group)) in levels.iter().enumerate() {
+        if i > 0 && *group != levels[i - 1].2 {
+            runs += 1;
+        }
+        priorities.push(runs);
+    }
+
+    let mut tools: Vec<(&str, Vec<String>)> = Vec::new();
+    for (i, (name, level, _)) in levels.iter().enumerate() {
+        let (tool, short_name) = name.split_once("::").unwrap_or(("rust", name));
+        let priority = i64::from(priorities[i]) - i64::from(runs);
+        let mut entries = Vec::new();
+        if priority != 0 || (name == "unexpected_cfgs" && !check_cfg.is_empty()) {
+            entries.push(format!("level = \"{}\"", level));
+        }
+        if priority != 0 {
+            entries.push(format!("priority = {}", priority));
+        }
+        if name == "unexpected_cfgs" && !check_cfg.is_empty() {
+            let specs: Vec<String> = check_cfg.iter().map(|spec| quote(spec)).collect();
+            entries.push(format!("check-cfg = [{}]", specs.join(", ")));
+        }
+        let line = if entries.is_empty() {
+            format!("{} = \"{}\"", short_name, level)
+        } else {
+            format!("{} = {{ {} }}", short_name, entries.join(", "))
+        };
+        match tools.iter_mut().find(|(existing, _)| *existing == tool) {
+            Some((_, lines)) => lines.push(line),
+            None => tools.push((tool, vec![line])),
+        }
+    }
+    tools.sort_by_key(|(tool, _)| match *tool {
+        "rust" => 0,
+        "clippy" => 1,
+        "rustdoc" => 2,
+        _ => 3,
+    });
+
+    let mut output = String::new();
+    for (tool, lines) in tools {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        let _ = writeln!(output, "[lints.{}]", tool);
+        for line in lines {
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn lint_config(tool: &str, name: &str, config: &Value) -> Result<(&'static str, i64), Error> {
+    let (level, priority) = match config {
+        Value::String(level) => (level.as_str(), 0),
+        Value::Table(table) => {
+            let level = table
+                .get("level")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid(format!("missing `level` for `lints.{}.{}`", tool, name)))?;
+            let priority = match table.get("priority") {
+                None => 0,
+                Some(Value::Integer(priority)) => *priority,
+                Some(_) => {
+                    return Err(invalid(format!(
+                        "expected an integer `priority` for `lints.{}.{}`",
+                        tool, name
+                    )));
+                }
+            };
+            check_keys(tool, name, table)?;
+            (level, priority)
+        }
+        _ => {
+            return Err(invalid(format!(
+                "expected a string or table for `lints.{}.{}`",
+                tool, name
+            )));
+        }
+    };
+    let level = match level {
+        "forbid" => "forbid",
+        "deny" => "deny",
+        "warn" => "warn",
+        "allow" => "allow",
+        _ => {
+            return Err(invalid(format!(
+                "unknown lint level `{}` for `lints.{}.{}`",
+                level, tool, name
+            )));
+        }
+    };
+    Ok((level, priority))
+}
+
+fn check_keys(tool: &str, name: &str, table: &Table) -> Result<(), Error> {
+    for key in table.keys() {
+        let allowed = match key.as_str() {
+            "level" | "priority" => true,
+            "check-cfg" => tool == "rust" && name == "unexpected_cfgs",
+            _ => false,
+        };
+        if !allowed {
+            return Err(invalid(format!(
+                "unexpected key `{}` in `lints.{}.{}`",
+                key, tool, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_cfg_list(value: &Value) -> Result<Vec<String>, Error> {
+    let expected = || invalid("expected `check-cfg` to be an array of strings".to_owned());
+    let Value::Array(array) = value else {
+        return Err(expected());
+    };
+    array
+        .iter()
+        .map(|spec| spec.as_str().map(str::to_owned).ok_or_else(expected))
+        .collect()
+}
+
+// Lint groups that commonly appear alongside lints they contain. Cargo
+// requires these to have a lower priority than the individual lints in order
+// for the individual lints to take effect.
+pub(crate) fn is_group(name: &str) -> bool {
+    match name.split_once("::") {
+        None => matches!(
+            name,
+            "warnings"
+                | "unused"
+                | "nonstandard_style"
+                | "future_incompatible"
+                | "let_underscore"
+                | "keyword_idents"
+                | "refining_impl_trait"
+                | "rust_2018_idioms"
+                | "rust_2018_compatibility"
+                | "rust_2021_compatibility"
+                | "rust_2024_compatibility"
+        ),
+        Some(("clippy", lint)) => matches!(
+            lint,
+            "all"
+                | "correctness"
+                | "suspicious"
+                | "style"
+                | "complexity"
+                | "perf"
+                | "pedantic"
+                | "restriction"
+                | "nursery"
+                | "cargo"
+        ),
+        Some(("rustdoc", lint)) => lint == "all",
+        Some(_) => false,
+    }
+}
+
+// TOML literal string, or basic string if the value contains a single quote.
+fn quote(string: &str) -> String {
+    if !string.contains('\'') {
+        return format!("'{}'", string);
+    }
+    let mut quoted = String::from("\"");
+    for ch in string.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::Manifest(message))
+}
@@ -0,0 +1,340 @@
+use crate::{Flag, LibraryKind, LinkKind, LinkModifier, LinkModifierPrefix, LinkerFlavor};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// **Native library requested by a `-l` flag**
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct NativeLibrary {
+    /// Name of the library as written in the `-l` flag.
+    pub name: String,
+
+    /// Whether the library is linked statically, dynamically, or as a
+    /// framework.
+    pub kind: LinkKind,
+
+    /// Whether `+verbatim` was given, meaning `name` is the exact file name.
+    pub verbatim: bool,
+
+    /// The file or framework directory the linker would pick up, or `None` if
+    /// none of the search paths contain a matching file.
+    pub path: Option<PathBuf>,
+}
+
+/// Locate the native libraries linked by `-l` flags among the directories
+/// given by `-L` flags.
+///
+/// The file names considered are the ones the linker for `target` would look
+/// for, such as `libfoo.so` or `libfoo.a` for `-l foo` on Linux, `foo.lib` on
+/// MSVC, and `Foo.framework` for `-l framework=Foo`. Only `-L` directories of
+/// a kind that applies to native libraries are searched, in the order they
+/// appear. Directories that the linker searches by default, like `/usr/lib`,
+/// are not considered. For `-l foo:bar` the library searched for is `bar`.
+pub fn find_native_libraries(flags: &[Flag], target: &str) -> Vec<NativeLibrary> {
+    let mut libraries = Vec::new();
+
+    for flag in flags {
+        let Flag::Link {
+            kind,
+            modifiers,
+            name,
+            rename,
+        } = flag
+        else {
+            continue;
+        };
+
+        let mut verbatim = false;
+        for (prefix, modifier) in modifiers {
+            if *modifier == LinkModifier::Verbatim {
+                verbatim = *prefix == LinkModifierPrefix::Enable;
+            }
+        }
+
+        let linked = rename.as_deref().unwrap_or(name);
+        let path = find(flags, linked, *kind, verbatim, target);
+
+        libraries.push(NativeLibrary {
+            name: name.clone(),
+            kind: *kind,
+            verbatim,
+            path,
+        });
+    }
+
+    libraries
+}
+
+fn find(
+    flags: &[Flag],
+    name: &str,
+    kind: LinkKind,
+    verbatim: bool,
+    target: &str,
+) -> Option<PathBuf> {
+    let candidates = candidates(name, kind, verbatim, target);
+    search_paths(flags, kind).find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| dir.join(candidate))
+            .find(|path| path.exists())
+    })
+}
+
+fn search_paths(flags: &[Flag], link_kind: LinkKind) -> impl Iterator<Item = &Path> {
+    flags.iter().filter_map(move |flag| match flag {
+        Flag::LibrarySearchPath { kind, path } => {
+            let applies = match kind {
+                LibraryKind::All => true,
+                LibraryKind::Native => link_kind != LinkKind::Framework,
+                LibraryKind::Framework => link_kind == LinkKind::Framework,
+                LibraryKind::Dependency | LibraryKind::Crate => false,
+            };
+            if applies {
+                Some(path.as_path())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })
+}
+
+// File names in the order the linker prefers them within one directory.
+fn candidates(name: &str, kind: LinkKind, verbatim: bool, target: &str) -> Vec<String> {
+    if verbatim {
+        return vec![name.to_owned()];
+    }
+
+    if kind == LinkKind::Framework {
+        return vec![format!("{}.framework", name)];
+    }
+
+    let msvc = target.ends_with("-msvc");
+    let windows = target.contains("-windows");
+    let apple = target.contains("-apple-");
+
+    let candidates: &[(&str, &str)] = match kind {
+        LinkKind::Static if msvc => &[("", ".lib"), ("lib", ".a")],
+        LinkKind::Static => &[("lib", ".a"), ("", ".lib")],
+        LinkKind::Dylib if msvc => &[("", ".dll.lib"), ("", ".lib")],
+        LinkKind::Dylib if windows => &[
+            ("lib", ".dll.a"),
+            ("", ".dll.a"),
+            ("lib", ".a"),
+            ("", ".lib"),
+            ("", ".dll"),
+            ("lib", ".dll"),
+        ],
+        LinkKind::Dylib if apple => &[("lib", ".tbd"), ("lib", ".dylib"), ("lib", ".a")],
+        LinkKind::Dylib | LinkKind::Framework => &[("lib", ".so"), ("lib", ".a")],
+    };
+
+    candidates
+        .iter()
+        .map(|(prefix, suffix)| format!("{}{}{}", prefix, name, suffix))
+        .collect()
+}
+
+/// Translate `-L` and `-l` flags into arguments for linking a C program
+/// against the same native libraries.
+///
+/// The syntax follows `flavor`: [`LinkerFlavor::Gcc`] and
+/// [`LinkerFlavor::Em`] produce arguments for a C compiler driver (`-L`, `-l`,
+/// `-Wl,--whole-archive`), [`LinkerFlavor::Msvc`] and
+/// [`LinkerFlavor::LldLink`] produce arguments for `link.exe` (`/LIBPATH:`,
+/// `foo.lib`), and the remaining flavors produce arguments for invoking `ld`
+/// directly.
+///
+/// Link modifiers are honored the same way rustc honors them for `target`:
+/// static libraries with `+whole-archive` are wrapped in `--whole-archive`,
+/// dynamic libraries with `-as-needed` in `--no-as-needed`, and `+verbatim`
+/// names are passed through as exact file names. Apple's linker has none of
+/// these options, so on Apple targets `-Bstatic`, `-Bdynamic` and
+/// `--as-needed` are left out, and `+whole-archive` libraries are passed with
+/// `-force_load` and the path found by [`find_native_libraries`].
+pub fn c_linker_args(flags: &[Flag], flavor: LinkerFlavor, target: &str) -> Vec<OsString> {
+    linker_args(flags, flavor, target, true)
+}
+
+/// Like [`c_linker_args`], but for a C program that also links the Rust crate
+/// built as a `staticlib`.
+///
+/// Static libraries with `+bundle` (the default for `-l static=…`) are bundled
+/// into the staticlib by rustc, so they are left out to avoid linking them
+/// twice. Libraries with `-bundle` still need to be linked by the C program.
+pub fn c_linker_args_for_staticlib(
+    flags: &[Flag],
+    flavor: LinkerFlavor,
+    target: &str,
+) -> Vec<OsString> {
+    linker_args(flags, flavor, target, false)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Syntax {
+    Driver,
+    Ld,
+    Msvc,
+}
+
+fn linker_args(
+    flags: &[Flag],
+    flavor: LinkerFlavor,
+    target: &str,
+    include_bundled: bool,
+) -> Vec<OsString> {
+    let syntax = match flavor {
+        LinkerFlavor::Gcc | LinkerFlavor::Em => Syntax::Driver,
+        LinkerFlavor::Msvc | LinkerFlavor::LldLink => Syntax::Msvc,
+        _ => Syntax::Ld,
+    };
+    // Apple's ld64 does not take the GNU options for static or dynamic
+    // linking, nor `-l:` for exact file names.
+    let gnu = !target.contains("-apple-");
+
+    let linker_arg = |arg: &str| -> OsString {
+        if syntax == Syntax::Driver {
+            OsString::from(format!("-Wl,{}", arg))
+        } else {
+            OsString::from(arg)
+        }
+    };
+
+    let mut args = Vec::new();
+
+    for flag in flags {
+        if let Flag::LibrarySearchPath { kind, path } = flag {
+            let prefix = match (syntax, kind) {
+                (_, LibraryKind::Dependency | LibraryKind::Crate)
+                | (Syntax::Msvc, LibraryKind::Framework) => continue,
+                (Syntax::Msvc, _) => "/LIBPATH:",
+                (_, LibraryKind::Framework) => "-F",
+                (_, LibraryKind::Native | LibraryKind::All) => "-L",
+            };
+            let mut arg = OsString::from(prefix);
+            arg.push(path);
+            args.push(arg);
+        }
+    }
+
+    let mut hint_static = false;
+    let mut as_needed_emitted = false;
+
+    for flag in flags {
+        let Flag::Link {
+            kind,
+            modifiers,
+            name,
+            rename,
+        } = flag
+        else {
+            continue;
+        };
+        let name = rename.as_deref().unwrap_or(name);
+
+        let mut bundle = true;
+        let mut verbatim = false;
+        let mut whole_archive = false;
+        let mut as_needed = true;
+        for (prefix, modifier) in modifiers {
+            let enable = *prefix == LinkModifierPrefix::Enable;
+            match modifier {
+                LinkModifier::Bundle => bundle = enable,
+                LinkModifier::Verbatim => verbatim = enable,
+                LinkModifier::WholeArchive => whole_archive = enable,
+                LinkModifier::AsNeeded => as_needed = enable,
+            }
+        }
+
+        if syntax == Syntax::Msvc {
+            let file = if verbatim {
+                name.to_owned()
+            } else {
+                format!("{}.lib", name)
+            };
+            match kind {
+                LinkKind::Static if bundle && !include_bundled => {}
+                LinkKind::Static if whole_archive => {
+                    args.push(OsString::from(format!("/WHOLEARCHIVE:{}", file)));
+                }
+                LinkKind::Static | LinkKind::Dylib => args.push(OsString::from(file)),
+                LinkKind::Framework => {}
+            }
+            continue;
+        }
+
+        let lib = if verbatim && gnu {
+            OsString::from(format!("-l:{}", name))
+        } else {
+            OsString::from(format!("-l{}", name))
+        };
+
+        match kind {
+            LinkKind::Static => {
+                if bundle && !include_bundled {
+                    continue;
+                }
+                if !gnu {
+                    let path = whole_archive
+                        .then(|| find(flags, name, LinkKind::Static, verbatim, target))
+                        .flatten();
+                    match (path, syntax) {
+                        (Some(path), Syntax::Driver) => {
+                            let mut arg = OsString::from("-Wl,-force_load,");
+                            arg.push(path);
+                            args.push(arg);
+                        }
+                        (Some(path), _) => {
+                            args.push(OsString::from("-force_load"));
+                            args.push(path.into_os_string());
+                        }
+                        (None, _) => args.push(lib),
+                    }
+                    continue;
+                }
+                if !hint_static {
+                    args.push(linker_arg("-Bstatic"));
+                    hint_static = true;
+                }
+                if whole_archive {
+                    args.push(linker_arg("--whole-archive"));
+                    args.push(lib);
+                    args.push(linker_arg("--no-whole-archive"));
+                } else {
+                    args.push(lib);
+                }
+            }
+            LinkKind::Dylib if !gnu => args.push(lib),
+            LinkKind::Dylib => {
+                if !as_needed_emitted {
+                    // Rustc links dynamic libraries with --as-needed unless the
+                    // library opts out with -as-needed.
+                    args.push(linker_arg("--as-needed"));
+                    as_needed_emitted = true;
+                }
+                if hint_static {
+                    args.push(linker_arg("-Bdynamic"));
+                    hint_static = false;
+                }
+                if as_needed {
+                    args.push(lib);
+                } else {
+                    args.push(linker_arg("--no-as-needed"));
+                    args.push(lib);
+                    args.push(linker_arg("--as-needed"));
+                }
+            }
+            LinkKind::Framework => {
+                args.push(OsString::from("-framework"));
+                args.push(OsString::from(name));
+            }
+        }
+    }
+
+    if hint_static {
+        args.push(linker_arg("-Bdynamic"));
+    }
+
+    args
+}
@@ -1,15 +1,50 @@
-use crate::string::{EnvChar, EnvStr};
-use crate::{Flag, RustFlags};
+use crate::string::{EnvChar, EnvStr, EnvString};
+use crate::{Category, Flag};
+use std::ffi::OsString;
 use std::str;
 
-enum FlagConstructor {
-    Flag(Flag),
-    Opt(fn(&EnvStr) -> Option<Flag>),
-    Repeated(fn(&EnvStr) -> Option<(Flag, usize)>),
+pub(crate) enum FlagConstructor<T> {
+    Flag(T),
+    Opt(fn(&EnvStr) -> Option<T>),
+    Repeated(fn(&EnvStr) -> Option<(T, usize)>),
     Unrecognized,
 }
 
-mod opt {
+// Flag vocabulary of a command line tool, for `parse` to dispatch on.
+pub(crate) trait Lookup: Sized {
+    fn lookup_short(ch: char) -> FlagConstructor<Self>;
+    fn lookup_long(name: &str) -> FlagConstructor<Self>;
+}
+
+impl Lookup for Flag {
+    fn lookup_short(ch: char) -> FlagConstructor<Self> {
+        lookup_short(ch)
+    }
+
+    fn lookup_long(name: &str) -> FlagConstructor<Self> {
+        lookup_long(name)
+    }
+}
+
+pub(crate) struct Parser<T> {
+    pub encoded: EnvString,
+    pub pos: usize,
+    pub repeat: Option<(fn(&EnvStr) -> Option<(T, usize)>, usize)>,
+    pub short: bool,
+}
+
+impl<T> Parser<T> {
+    pub fn new(encoded: OsString) -> Self {
+        Parser {
+            encoded: EnvString::new(encoded),
+            pos: 0,
+            repeat: None,
+            short: false,
+        }
+    }
+}
+
+pub(crate) mod opt {
     use crate::string::EnvStr;
     use crate::{
         Color, CrateType, Emit, ErrorFormat, Flag, LibraryKind, LinkKind, LinkModifier,
@@ -19,7 +54,7 @@ mod opt {
     use std::mem;
     use std::path::PathBuf;
 
-    pub(crate) fn cfg(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn cfg<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let (name, value) = match arg.split_once('=') {
             Some((name, value)) => {
@@ -34,10 +69,15 @@ mod opt {
         };
         let name = name.to_owned();
         let value = value.map(str::to_owned);
-        Some(Flag::Cfg { name, value })
+        Some(T::from(Flag::Cfg { name, value }))
     }
 
-    pub(crate) fn library_search_path(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn check_cfg<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
+        let arg = arg.to_str()?;
+        Some(T::from(Flag::CheckCfg(arg.to_owned())))
+    }
+
+    pub(crate) fn library_search_path<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let (kind, path) = if let Some((kind, path)) = arg.split_once('=') {
             let kind = match kind.to_str()? {
                 "dependency" => LibraryKind::Dependency,
@@ -52,10 +92,10 @@ mod opt {
             (LibraryKind::All, arg)
         };
         let path = PathBuf::from(path);
-        Some(Flag::LibrarySearchPath { kind, path })
+        Some(T::from(Flag::LibrarySearchPath { kind, path }))
     }
 
-    pub(crate) fn link(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn link<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let mut modifiers = Vec::new();
         let (kind, name) = match arg.split_once('=') {
@@ -94,15 +134,15 @@ mod opt {
         };
         let name = name.to_owned();
         let rename = rename.map(str::to_owned);
-        Some(Flag::Link {
+        Some(T::from(Flag::Link {
             kind,
             modifiers,
             name,
             rename,
-        })
+        }))
     }
 
-    pub(crate) fn crate_type(mut arg: &EnvStr) -> Option<(Flag, usize)> {
+    pub(crate) fn crate_type<T: From<Flag>>(mut arg: &EnvStr) -> Option<(T, usize)> {
         while !arg.is_empty() {
             let first = match arg.split_once(',') {
                 Some((first, rest)) => {
@@ -124,22 +164,22 @@ mod opt {
                 "proc-macro" => CrateType::ProcMacro,
                 _ => continue,
             };
-            return Some((Flag::CrateType(crate_type), arg.len()));
+            return Some((T::from(Flag::CrateType(crate_type)), arg.len()));
         }
         None
     }
 
-    pub(crate) fn crate_name(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn crate_name<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::CrateName(arg.to_owned()))
+        Some(T::from(Flag::CrateName(arg.to_owned())))
     }
 
-    pub(crate) fn edition(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn edition<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        arg.parse().ok().map(Flag::Edition)
+        arg.parse().ok().map(Flag::Edition).map(T::from)
     }
 
-    pub(crate) fn emit(mut arg: &EnvStr) -> Option<(Flag, usize)> {
+    pub(crate) fn emit<T: From<Flag>>(mut arg: &EnvStr) -> Option<(T, usize)> {
         while !arg.is_empty() {
             let first = match arg.split_once(',') {
                 Some((first, rest)) => {
@@ -162,60 +202,60 @@ mod opt {
                 "mir" => Emit::Mir,
                 _ => continue,
             };
-            return Some((Flag::Emit(emit), arg.len()));
+            return Some((T::from(Flag::Emit(emit)), arg.len()));
         }
         None
     }
 
-    pub(crate) fn print(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn print<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Print(arg.to_owned()))
+        Some(T::from(Flag::Print(arg.to_owned())))
     }
 
-    pub(crate) fn out(arg: &EnvStr) -> Option<Flag> {
-        Some(Flag::Out(PathBuf::from(arg)))
+    pub(crate) fn out<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
+        Some(T::from(Flag::Out(PathBuf::from(arg))))
     }
 
-    pub(crate) fn out_dir(arg: &EnvStr) -> Option<Flag> {
-        Some(Flag::OutDir(PathBuf::from(arg)))
+    pub(crate) fn out_dir<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
+        Some(T::from(Flag::OutDir(PathBuf::from(arg))))
     }
 
-    pub(crate) fn explain(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn explain<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Explain(arg.to_owned()))
+        Some(T::from(Flag::Explain(arg.to_owned())))
     }
 
-    pub(crate) fn target(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn target<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Target(arg.to_owned()))
+        Some(T::from(Flag::Target(arg.to_owned())))
     }
 
-    pub(crate) fn allow(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn allow<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Allow(arg.to_owned()))
+        Some(T::from(Flag::Allow(arg.to_owned())))
     }
 
-    pub(crate) fn warn(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn warn<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Warn(arg.to_owned()))
+        Some(T::from(Flag::Warn(arg.to_owned())))
     }
 
-    pub(crate) fn force_warn(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn force_warn<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::ForceWarn(arg.to_owned()))
+        Some(T::from(Flag::ForceWarn(arg.to_owned())))
     }
 
-    pub(crate) fn deny(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn deny<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Deny(arg.to_owned()))
+        Some(T::from(Flag::Deny(arg.to_owned())))
     }
 
-    pub(crate) fn forbid(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn forbid<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Forbid(arg.to_owned()))
+        Some(T::from(Flag::Forbid(arg.to_owned())))
     }
 
-    pub(crate) fn cap_lints(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn cap_lints<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let level = match arg {
             "allow" => LintLevel::Allow,
@@ -224,10 +264,10 @@ mod opt {
             "forbid" => LintLevel::Forbid,
             _ => return None,
         };
-        Some(Flag::CapLints(level))
+        Some(T::from(Flag::CapLints(level)))
     }
 
-    pub(crate) fn codegen(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn codegen<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let (opt, value) = match arg.split_once('=') {
             Some((opt, value)) => (opt, Some(value)),
@@ -235,36 +275,36 @@ mod opt {
         };
         let opt = opt.to_owned();
         let value = value.map(str::to_owned);
-        Some(Flag::Codegen { opt, value })
+        Some(T::from(Flag::Codegen { opt, value }))
     }
 
-    pub(crate) fn extern_(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn extern_<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let (name, path) = match arg.split_once('=') {
             Some((name, path)) => (name, Some(path)),
             None => (arg, None),
         };
         let name = name.to_str()?.to_owned();
         let path = path.map(PathBuf::from);
-        Some(Flag::Extern { name, path })
+        Some(T::from(Flag::Extern { name, path }))
     }
 
-    pub(crate) fn extern_location(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn extern_location<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let (name, location) = arg.split_once('=')?;
         let name = name.to_str()?.to_owned();
         let location = OsString::from(location);
-        Some(Flag::ExternLocation { name, location })
+        Some(T::from(Flag::ExternLocation { name, location }))
     }
 
-    pub(crate) fn sysroot(arg: &EnvStr) -> Option<Flag> {
-        Some(Flag::Sysroot(PathBuf::from(arg)))
+    pub(crate) fn sysroot<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
+        Some(T::from(Flag::Sysroot(PathBuf::from(arg))))
     }
 
-    pub(crate) fn z(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn z<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Z(arg.to_owned()))
+        Some(T::from(Flag::Z(arg.to_owned())))
     }
 
-    pub(crate) fn error_format(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn error_format<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let format = match arg {
             "human" => ErrorFormat::Human,
@@ -272,15 +312,15 @@ mod opt {
             "short" => ErrorFormat::Short,
             _ => return None,
         };
-        Some(Flag::ErrorFormat(format))
+        Some(T::from(Flag::ErrorFormat(format)))
     }
 
-    pub(crate) fn json(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn json<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
-        Some(Flag::Json(arg.to_owned()))
+        Some(T::from(Flag::Json(arg.to_owned())))
     }
 
-    pub(crate) fn color(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn color<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let arg = arg.to_str()?;
         let color = match arg {
             "auto" => Color::Auto,
@@ -288,47 +328,48 @@ mod opt {
             "never" => Color::Never,
             _ => return None,
         };
-        Some(Flag::Color(color))
+        Some(T::from(Flag::Color(color)))
     }
 
-    pub(crate) fn remap_path_prefix(arg: &EnvStr) -> Option<Flag> {
+    pub(crate) fn remap_path_prefix<T: From<Flag>>(arg: &EnvStr) -> Option<T> {
         let (from, to) = arg.split_once('=')?;
         let from = PathBuf::from(from);
         let to = PathBuf::from(to);
-        Some(Flag::RemapPathPrefix { from, to })
+        Some(T::from(Flag::RemapPathPrefix { from, to }))
     }
 }
 
-fn lookup_short(ch: char) -> FlagConstructor {
+pub(crate) fn lookup_short<T: From<Flag>>(ch: char) -> FlagConstructor<T> {
     match ch {
-        'h' => FlagConstructor::Flag(Flag::Help),
+        'h' => FlagConstructor::Flag(T::from(Flag::Help)),
         'L' => FlagConstructor::Opt(opt::library_search_path),
         'l' => FlagConstructor::Opt(opt::link),
-        'g' => FlagConstructor::Flag(Flag::Codegen {
+        'g' => FlagConstructor::Flag(T::from(Flag::Codegen {
             opt: "debuginfo".to_owned(),
             value: Some("2".to_owned()),
-        }),
-        'O' => FlagConstructor::Flag(Flag::Codegen {
+        })),
+        'O' => FlagConstructor::Flag(T::from(Flag::Codegen {
             opt: "opt-level".to_owned(),
             value: Some("2".to_owned()),
-        }),
+        })),
         'o' => FlagConstructor::Opt(opt::out),
         'A' => FlagConstructor::Opt(opt::allow),
         'W' => FlagConstructor::Opt(opt::warn),
         'D' => FlagConstructor::Opt(opt::deny),
         'F' => FlagConstructor::Opt(opt::forbid),
         'C' => FlagConstructor::Opt(opt::codegen),
-        'V' => FlagConstructor::Flag(Flag::Version),
-        'v' => FlagConstructor::Flag(Flag::Verbose),
+        'V' => FlagConstructor::Flag(T::from(Flag::Version)),
+        'v' => FlagConstructor::Flag(T::from(Flag::Verbose)),
         'Z' => FlagConstructor::Opt(opt::z),
         _ => FlagConstructor::Unrecognized,
     }
 }
 
-fn lookup_long(name: &str) -> FlagConstructor {
+pub(crate) fn lookup_long<T: From<Flag>>(name: &str) -> FlagConstructor<T> {
     match name {
-        "help" => FlagConstructor::Flag(Flag::Help),
+        "help" => FlagConstructor::Flag(T::from(Flag::Help)),
         "cfg" => FlagConstructor::Opt(opt::cfg),
+        "check-cfg" => FlagConstructor::Opt(opt::check_cfg),
         "crate-type" => FlagConstructor::Repeated(opt::crate_type),
         "crate-name" => FlagConstructor::Opt(opt::crate_name),
         "edition" => FlagConstructor::Opt(opt::edition),
@@ -336,7 +377,7 @@ fn lookup_long(name: &str) -> FlagConstructor {
         "print" => FlagConstructor::Opt(opt::print),
         "out-dir" => FlagConstructor::Opt(opt::out_dir),
         "explain" => FlagConstructor::Opt(opt::explain),
-        "test" => FlagConstructor::Flag(Flag::Test),
+        "test" => FlagConstructor::Flag(T::from(Flag::Test)),
         "target" => FlagConstructor::Opt(opt::target),
         "allow" => FlagConstructor::Opt(opt::allow),
         "warn" => FlagConstructor::Opt(opt::warn),
@@ -345,8 +386,8 @@ fn lookup_long(name: &str) -> FlagConstructor {
         "forbid" => FlagConstructor::Opt(opt::forbid),
         "cap-lints" => FlagConstructor::Opt(opt::cap_lints),
         "codegen" => FlagConstructor::Opt(opt::codegen),
-        "version" => FlagConstructor::Flag(Flag::Version),
-        "verbose" => FlagConstructor::Flag(Flag::Verbose),
+        "version" => FlagConstructor::Flag(T::from(Flag::Version)),
+        "verbose" => FlagConstructor::Flag(T::from(Flag::Verbose)),
         "extern" => FlagConstructor::Opt(opt::extern_),
         "extern-location" => FlagConstructor::Opt(opt::extern_location),
         "sysroot" => FlagConstructor::Opt(opt::sysroot),
@@ -358,7 +399,83 @@ fn lookup_long(name: &str) -> FlagConstructor {
     }
 }
 
-pub(crate) fn parse(f: &mut RustFlags) -> Option<Flag> {
+impl Flag {
+    /// Aspects of compilation affected by this flag.
+    ///
+    /// ```
+    /// use rustflags::{Category, Flag};
+    ///
+    /// let flag = Flag::Codegen {
+    ///     opt: "target-feature".to_owned(),
+    ///     value: Some("+avx2".to_owned()),
+    /// };
+    /// assert_eq!(flag.categories(), [Category::Codegen, Category::Cfg]);
+    /// ```
+    pub fn categories(&self) -> &'static [Category] {
+        match self {
+            Flag::Help | Flag::Print(_) | Flag::Explain(_) | Flag::Version => {
+                &[Category::Informational]
+            }
+            Flag::Cfg { .. } => &[Category::Cfg],
+            Flag::CheckCfg(_)
+            | Flag::Allow(_)
+            | Flag::Warn(_)
+            | Flag::ForceWarn(_)
+            | Flag::Deny(_)
+            | Flag::Forbid(_)
+            | Flag::CapLints(_)
+            | Flag::Verbose
+            | Flag::ExternLocation { .. }
+            | Flag::ErrorFormat(_)
+            | Flag::Json(_)
+            | Flag::Color(_) => &[Category::Diagnostics],
+            Flag::LibrarySearchPath { .. } | Flag::Link { .. } => &[Category::Linking],
+            Flag::CrateType(_) => &[Category::Codegen, Category::Output, Category::Linking],
+            Flag::CrateName(_) => &[Category::Codegen, Category::Output],
+            Flag::Emit(_) | Flag::Out(_) | Flag::OutDir(_) => &[Category::Output],
+            Flag::Test => &[Category::Codegen, Category::Cfg],
+            Flag::Target(_) => &[Category::Codegen, Category::Linking, Category::Cfg],
+            Flag::Codegen { opt, value: _ } => codegen_categories(opt),
+            Flag::Extern { .. } | Flag::Sysroot(_) => &[Category::Codegen, Category::Linking],
+            Flag::Edition(_) | Flag::Z(_) => &[Category::Codegen],
+            Flag::RemapPathPrefix { .. } => &[Category::Codegen, Category::Diagnostics],
+        }
+    }
+}
+
+// Rustc accepts `_` in place of `-` in the names of `-C` and `-Z` options.
+pub(crate) fn option_name(opt: &str) -> String {
+    opt.replace('_', "-")
+}
+
+fn codegen_categories(opt: &str) -> &'static [Category] {
+    match option_name(opt).as_str() {
+        "ar"
+        | "default-linker-libraries"
+        | "link-arg"
+        | "link-args"
+        | "link-dead-code"
+        | "link-self-contained"
+        | "linker"
+        | "linker-flavor"
+        | "pre-link-arg"
+        | "pre-link-args"
+        | "prefer-dynamic"
+        | "relro-level"
+        | "rpath"
+        | "strip" => &[Category::Linking],
+        "lto" | "linker-plugin-lto" => &[Category::Codegen, Category::Linking],
+        "debug-assertions" | "overflow-checks" | "panic" | "target-feature" => {
+            &[Category::Codegen, Category::Cfg]
+        }
+        "relocation-model" => &[Category::Codegen, Category::Linking, Category::Cfg],
+        "extra-filename" | "incremental" | "save-temps" => &[Category::Output],
+        "remark" => &[Category::Diagnostics],
+        _ => &[Category::Codegen],
+    }
+}
+
+pub(crate) fn parse<T: Lookup>(f: &mut Parser<T>) -> Option<T> {
     const SEPARATOR: char = '\x1F';
 
     let mut skip = false;
@@ -391,7 +508,7 @@ pub(crate) fn parse(f: &mut RustFlags) -> Option<Flag> {
                 }
                 EnvChar::Invalid => '\0',
             };
-            let constructor = match lookup_short(ch) {
+            let constructor = match T::lookup_short(ch) {
                 FlagConstructor::Flag(flag) => return Some(flag),
                 FlagConstructor::Opt(f) => ConstructorFn::Opt(f),
                 FlagConstructor::Repeated(f) => ConstructorFn::Repeated(f),
@@ -458,7 +575,7 @@ pub(crate) fn parse(f: &mut RustFlags) -> Option<Flag> {
                     let Some(name) = name.to_str() else {
                         continue;
                     };
-                    let constructor = match lookup_long(name) {
+                    let constructor = match T::lookup_long(name) {
                         // `--flag`
                         FlagConstructor::Flag(flag) if arg.is_none() => return Some(flag),
                         FlagConstructor::Opt(f) => ConstructorFn::Opt(f),
@@ -493,9 +610,9 @@ pub(crate) fn parse(f: &mut RustFlags) -> Option<Flag> {
             continue;
         };
 
-        enum ConstructorFn {
-            Opt(fn(&EnvStr) -> Option<Flag>),
-            Repeated(fn(&EnvStr) -> Option<(Flag, usize)>),
+        enum ConstructorFn<T> {
+            Opt(fn(&EnvStr) -> Option<T>),
+            Repeated(fn(&EnvStr) -> Option<(T, usize)>),
         }
 
         match constructor {
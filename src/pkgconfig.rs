@@ -0,0 +1,220 @@
+use crate::{Flag, LibraryKind, LinkKind, LinkModifier, LinkModifierPrefix};
+use std::mem;
+use std::path::PathBuf;
+
+/// Convert the output of `pkg-config --libs` (optionally with `--static` or
+/// `--cflags`) into rustc flags.
+///
+/// - `-L` and `-F` become [`Flag::LibrarySearchPath`] of kind `native` and
+///   `framework` respectively,
+/// - `-l` and `-framework` become [`Flag::Link`], with `-l:FILE` using the
+///   `+verbatim` modifier,
+/// - linker arguments, meaning `-Wl,...`, `-Xlinker ARG`, `-pthread`,
+///   `-rdynamic`, `-shared`, `-static...`, `-fopenmp` and paths of library
+///   files, become `-C link-arg`.
+///
+/// Every other option, such as the preprocessor and compiler options from
+/// `--cflags` (`-I`, `-D`, `-isystem`, `-O2`, `-fPIC`, ...), has no rustc
+/// equivalent and is skipped, together with its argument if that is given as
+/// a separate word.
+pub fn from_pkg_config(output: &str) -> Vec<Flag> {
+    let mut flags = Vec::new();
+    let mut tokens = split(output).into_iter();
+
+    while let Some(token) = tokens.next() {
+        if token == "-framework" {
+            if let Some(name) = tokens.next() {
+                flags.push(Flag::Link {
+                    kind: LinkKind::Framework,
+                    modifiers: Vec::new(),
+                    name,
+                    rename: None,
+                });
+            }
+        } else if let Some(path) = token.strip_prefix("-L") {
+            let path = match path {
+                "" => tokens.next().unwrap_or_default(),
+                path => path.to_owned(),
+            };
+            flags.push(Flag::LibrarySearchPath {
+                kind: LibraryKind::Native,
+                path: PathBuf::from(path),
+            });
+        } else if let Some(path) = token.strip_prefix("-F") {
+            let path = match path {
+                "" => tokens.next().unwrap_or_default(),
+                path => path.to_owned(),
+            };
+            flags.push(Flag::LibrarySearchPath {
+                kind: LibraryKind::Framework,
+                path: PathBuf::from(path),
+            });
+        } else if let Some(name) = token.strip_prefix("-l") {
+            let name = match name {
+                "" => tokens.next().unwrap_or_default(),
+                name => name.to_owned(),
+            };
+            let (modifiers, name) = match name.strip_prefix(':') {
+                Some(file) => (
+                    vec![(LinkModifierPrefix::Enable, LinkModifier::Verbatim)],
+                    file.to_owned(),
+                ),
+                None => (Vec::new(), name),
+            };
+            flags.push(Flag::Link {
+                kind: LinkKind::Dylib,
+                modifiers,
+                name,
+                rename: None,
+            });
+        } else if token == "-Xlinker" {
+            if let Some(arg) = tokens.next() {
+                flags.push(link_arg("-Xlinker".to_owned()));
+                flags.push(link_arg(arg));
+            }
+        } else if is_linker_arg(&token) {
+            flags.push(link_arg(token));
+        } else if matches!(
+            token.as_str(),
+            "-I" | "-D" | "-U" | "-isystem" | "-include" | "-iquote",
+        ) {
+            // Compiler option from --cflags, with its argument in the next
+            // word.
+            tokens.next();
+        } else {
+            // Compiler option from --cflags.
+        }
+    }
+
+    flags
+}
+
+fn is_linker_arg(token: &str) -> bool {
+    !token.starts_with('-')
+        || token.starts_with("-Wl,")
+        || token.starts_with("-static")
+        || matches!(token, "-pthread" | "-rdynamic" | "-shared" | "-fopenmp")
+}
+
+fn link_arg(arg: String) -> Flag {
+    Flag::Codegen {
+        opt: "link-arg".to_owned(),
+        value: Some(arg),
+    }
+}
+
+/// Render the linker-related flags as the `Libs:` line of a pkg-config `.pc`
+/// file.
+///
+/// This is the reverse of [`from_pkg_config`]. Flags that do not affect
+/// linking are ignored, as are `-L` directories of kind `dependency` and
+/// `crate` which only apply to Rust crates.
+pub fn to_pkg_config_libs(flags: &[Flag]) -> String {
+    let mut libs = String::from("Libs:");
+
+    for flag in flags {
+        match flag {
+            Flag::LibrarySearchPath { kind, path } => {
+                let option = match kind {
+                    LibraryKind::Native | LibraryKind::All => "-L",
+                    LibraryKind::Framework => "-F",
+                    LibraryKind::Dependency | LibraryKind::Crate => continue,
+                };
+                push(&mut libs, &format!("{}{}", option, path.display()));
+            }
+            Flag::Link {
+                kind,
+                modifiers,
+                name,
+                rename,
+            } => {
+                let name = rename.as_ref().unwrap_or(name);
+                if *kind == LinkKind::Framework {
+                    push(&mut libs, "-framework");
+                    push(&mut libs, name);
+                    continue;
+                }
+                let mut verbatim = false;
+                for (prefix, modifier) in modifiers {
+                    if *modifier == LinkModifier::Verbatim {
+                        verbatim = *prefix == LinkModifierPrefix::Enable;
+                    }
+                }
+                let colon = if verbatim { ":" } else { "" };
+                push(&mut libs, &format!("-l{}{}", colon, name));
+            }
+            Flag::Codegen {
+                opt,
+                value: Some(value),
+            } => match opt.as_str() {
+                "link-arg" => push(&mut libs, value),
+                "link-args" => {
+                    for arg in value.split_whitespace() {
+                        push(&mut libs, arg);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    libs
+}
+
+fn push(libs: &mut String, arg: &str) {
+    libs.push(' ');
+    for ch in arg.chars() {
+        if ch.is_whitespace() || matches!(ch, '\\' | '"' | '\'' | '#') {
+            libs.push('\\');
+        } else if ch == '$' {
+            // Variable references in .pc files start with '$'.
+            libs.push('$');
+        }
+        libs.push(ch);
+    }
+}
+
+// Split the way pkg-config's own output is meant to be split by a shell:
+// whitespace separates arguments, backslash escapes the next character, and
+// single or double quotes group characters.
+fn split(output: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut in_token = false;
+    let mut quote = None;
+    let mut chars = output.chars();
+
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(q), ch) if ch == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    token.push(escaped);
+                }
+                in_token = true;
+            }
+            (Some(_), ch) => token.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_token = true;
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if in_token {
+                    tokens.push(mem::take(&mut token));
+                    in_token = false;
+                }
+            }
+            (None, ch) => {
+                token.push(ch);
+                in_token = true;
+            }
+        }
+    }
+
+    if in_token {
+        tokens.push(token);
+    }
+
+    tokens
+}
@@ -0,0 +1,142 @@
+use crate::check::{Diagnostic, Severity};
+use crate::error::{Error, ErrorKind};
+use crate::toml::{self, Value};
+use crate::{from_encoded, Flag};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+/// **Organization-wide rules about which flags may be used**
+///
+/// A policy is written in TOML as two lists of flags:
+///
+/// ```toml
+/// deny = ["-Ctarget-cpu=native", "-Zbuild-std", "-Z*"]
+/// allow = ["-Zsanitizer=address"]
+/// ```
+///
+/// Each entry is parsed like RUSTFLAGS, so it matches regardless of how the
+/// flag is spelled: `-Ctarget-cpu=native` in the policy also catches
+/// `-C target-cpu=native` and `--codegen=target-cpu=native`. An entry without
+/// a value, like `-Zbuild-std`, matches the flag with any value, and a
+/// trailing `*` matches anything starting with what precedes it. Flags
+/// matched by `allow` are exempt from `deny`.
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    deny: Vec<Pattern>,
+    allow: Vec<Pattern>,
+}
+
+#[derive(Clone, Debug)]
+struct Pattern {
+    source: String,
+    rendered: String,
+    wildcard: bool,
+}
+
+impl Policy {
+    /// Parse a policy from the contents of a policy file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy is not valid TOML or does not consist
+    /// of `deny` and `allow` arrays of single rustc flags.
+    pub fn from_toml(policy: &str) -> Result<Self, Error> {
+        parse(policy).map_err(|message| Error::new(ErrorKind::Policy(message)))
+    }
+
+    /// Read a policy file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid policy.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|error| {
+            Error::new(ErrorKind::Io {
+                path: path.to_owned(),
+                error,
+            })
+        })?;
+        parse(&contents).map_err(|message| {
+            Error::new(ErrorKind::Config {
+                path: path.to_owned(),
+                message,
+            })
+        })
+    }
+
+    /// Report every flag that the policy denies, as an error.
+    pub fn check(&self, flags: &[Flag]) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for (i, flag) in flags.iter().enumerate() {
+            let rendered = flag.to_string();
+            let Some(pattern) = self.deny.iter().find(|pattern| pattern.matches(&rendered)) else {
+                continue;
+            };
+            if self.allow.iter().any(|pattern| pattern.matches(&rendered)) {
+                continue;
+            }
+            let message = if pattern.rendered == rendered {
+                format!("`{}` is denied by policy", rendered)
+            } else {
+                format!("`{}` is denied by policy (`{}`)", rendered, pattern.source)
+            };
+            diagnostics.push(Diagnostic::new(Severity::Error, &message, flags, &[i]));
+        }
+        diagnostics
+    }
+}
+
+impl Pattern {
+    fn matches(&self, rendered: &str) -> bool {
+        if self.wildcard {
+            rendered.starts_with(&self.rendered)
+        } else {
+            rendered == self.rendered
+                || rendered
+                    .strip_prefix(&self.rendered)
+                    .is_some_and(|rest| rest.starts_with('='))
+        }
+    }
+}
+
+fn parse(policy: &str) -> Result<Policy, String> {
+    let table = toml::parse(policy).map_err(|error| error.to_string())?;
+    let mut policy = Policy::default();
+    for (key, value) in &table {
+        let patterns = match key.as_str() {
+            "deny" => &mut policy.deny,
+            "allow" => &mut policy.allow,
+            _ => return Err(format!("unexpected key `{}`", key)),
+        };
+        let Value::Array(array) = value else {
+            return Err(format!("expected `{}` to be an array of strings", key));
+        };
+        for entry in array {
+            let entry = entry
+                .as_str()
+                .ok_or_else(|| format!("expected `{}` to be an array of strings", key))?;
+            patterns.push(pattern(entry)?);
+        }
+    }
+    Ok(policy)
+}
+
+fn pattern(entry: &str) -> Result<Pattern, String> {
+    let args: Vec<&str> = entry.split_whitespace().collect();
+    let flags: Vec<Flag> = from_encoded(OsStr::new(&args.join("\x1F"))).collect();
+    let [flag] = flags.as_slice() else {
+        return Err(format!("`{}` is not a single rustc flag", entry));
+    };
+    let mut rendered = flag.to_string();
+    let wildcard = rendered.ends_with('*');
+    if wildcard {
+        rendered.pop();
+    }
+    Ok(Pattern {
+        source: entry.to_owned(),
+        rendered,
+        wildcard,
+    })
+}
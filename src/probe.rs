@@ -0,0 +1,159 @@
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::{Category, Flag};
+use std::env;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// **Rustc invocation for test-compiling a snippet of code**
+///
+/// Build scripts that detect compiler features, in the style of `autocfg`,
+/// need to compile their probes with the same rustc and the same relevant
+/// flags as the crate being built: otherwise a probe may succeed for the host
+/// but the crate fail for the target, or a probe may fail for lack of
+/// `--sysroot` or `-Z` flags. At the same time, flags that choose what to
+/// emit and where to put it, like `--emit`, `-o`, `--out-dir`,
+/// `--crate-type`, `--crate-name`, `--test`, `--print` and
+/// `--error-format=json`, must be left out or the probe breaks.
+///
+/// ```no_run
+/// let probe = rustflags::ProbeCommand::from_env();
+/// if probe.probe("pub fn f() -> Option<u8> { [1u8].first().copied() }") {
+///     println!("cargo:rustc-cfg=has_copied");
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProbeCommand {
+    rustc: PathBuf,
+    wrapper: Option<PathBuf>,
+    target: Option<String>,
+    out_dir: Option<PathBuf>,
+    flags: Vec<Flag>,
+}
+
+impl ProbeCommand {
+    /// Probe command for use from within a build script.
+    ///
+    /// The compiler is `RUSTC`, run through `RUSTC_WRAPPER` if that is set
+    /// and non-empty. The flags come from `CARGO_ENCODED_RUSTFLAGS`, the
+    /// target from `TARGET`, and output goes to `OUT_DIR`.
+    pub fn from_env() -> Self {
+        ProbeCommand::from_env_with(ProcessEnv)
+    }
+
+    /// Probe command for the build described by `env`, in the same way as
+    /// [`ProbeCommand::from_env`].
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_env_with(env: impl EnvSource) -> Self {
+        let env: &dyn EnvSource = &env;
+        let encoded = env.var_os("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+        let flags: Vec<Flag> = crate::from_encoded(&encoded).collect();
+        let mut probe = ProbeCommand::new(
+            env.var_os("RUSTC").unwrap_or_else(|| "rustc".into()),
+            &flags,
+        );
+        probe.wrapper = env
+            .var_os("RUSTC_WRAPPER")
+            .filter(|wrapper| !wrapper.is_empty())
+            .map(PathBuf::from);
+        probe.target = env.var("TARGET").filter(|target| !target.is_empty());
+        probe.out_dir = env.var_os("OUT_DIR").map(PathBuf::from);
+        probe
+    }
+
+    /// Probe command running `rustc` with the relevant subset of `flags`.
+    pub fn new(rustc: impl Into<PathBuf>, flags: &[Flag]) -> Self {
+        ProbeCommand {
+            rustc: rustc.into(),
+            wrapper: None,
+            target: None,
+            out_dir: None,
+            flags: flags
+                .iter()
+                .filter(|flag| is_forwarded(flag))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Run rustc through a wrapper, like `sccache`.
+    pub fn wrapper(&mut self, wrapper: impl Into<PathBuf>) -> &mut Self {
+        self.wrapper = Some(wrapper.into());
+        self
+    }
+
+    /// Compile for `target`, unless the flags already contain `--target`.
+    pub fn target(&mut self, target: impl Into<String>) -> &mut Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Directory for the probe's output. Defaults to the system's temporary
+    /// directory.
+    pub fn out_dir(&mut self, out_dir: impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// The flags that are forwarded to rustc.
+    pub fn flags(&self) -> &[Flag] {
+        &self.flags
+    }
+
+    /// Command that compiles a library crate read from stdin, emitting only
+    /// metadata.
+    pub fn command(&self) -> Command {
+        let mut cmd = match &self.wrapper {
+            Some(wrapper) => {
+                let mut cmd = Command::new(wrapper);
+                cmd.arg(&self.rustc);
+                cmd
+            }
+            None => Command::new(&self.rustc),
+        };
+        cmd.arg("--crate-name=rustflags_probe");
+        cmd.arg("--crate-type=lib");
+        cmd.arg("--emit=metadata");
+        let mut out_dir = OsString::from("--out-dir=");
+        out_dir.push(self.out_dir.clone().unwrap_or_else(env::temp_dir));
+        cmd.arg(out_dir);
+        if let Some(target) = &self.target {
+            if !self
+                .flags
+                .iter()
+                .any(|flag| matches!(flag, Flag::Target(_)))
+            {
+                cmd.arg(format!("--target={}", target));
+            }
+        }
+        cmd.args(self.flags.iter().cloned().flatten());
+        cmd.arg("-");
+        cmd
+    }
+
+    /// Compile `source` as a library crate, and report whether it compiled
+    /// successfully.
+    pub fn probe(&self, source: &str) -> bool {
+        let mut cmd = self.command();
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let Ok(mut child) = cmd.spawn() else {
+            return false;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            // A compiler that exits without reading all of its input fails
+            // the probe through its exit status.
+            let _ = stdin.write_all(source.as_bytes());
+        }
+        child.wait().is_ok_and(|status| status.success())
+    }
+}
+
+fn is_forwarded(flag: &Flag) -> bool {
+    let categories = flag.categories();
+    !categories.contains(&Category::Output)
+        && !categories.contains(&Category::Informational)
+        && !matches!(flag, Flag::Test | Flag::ErrorFormat(_) | Flag::Json(_))
+}
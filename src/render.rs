@@ -1,6 +1,7 @@
 use crate::write::WriteFmt;
 use crate::{Flag, LibraryKind, LinkKind};
 use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display};
 
 impl IntoIterator for Flag {
     type Item = OsString;
@@ -23,6 +24,11 @@ impl IntoIterator for Flag {
                 }
             }
 
+            Flag::CheckCfg(spec) => {
+                flags.push(OsString::from("--check-cfg"));
+                flags.push(OsString::from(spec));
+            }
+
             Flag::LibrarySearchPath { kind, path } => {
                 flags.push(OsString::from("-L"));
                 if kind == LibraryKind::All {
@@ -208,6 +214,18 @@ impl IntoIterator for Flag {
     }
 }
 
+impl Display for Flag {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (i, arg) in self.clone().into_iter().enumerate() {
+            if i > 0 {
+                formatter.write_str(" ")?;
+            }
+            Display::fmt(&arg.to_string_lossy(), formatter)?;
+        }
+        Ok(())
+    }
+}
+
 fn kv(k: impl AsRef<OsStr>, v: impl AsRef<OsStr>) -> OsString {
     let k = k.as_ref();
     let v = v.as_ref();
@@ -218,7 +236,7 @@ fn kv(k: impl AsRef<OsStr>, v: impl AsRef<OsStr>) -> OsString {
     string
 }
 
-mod iter {
+pub(crate) mod iter {
     use std::ffi::OsString;
     use std::vec;
 
@@ -0,0 +1,403 @@
+use crate::env_source::{EnvSource, ProcessEnv};
+use crate::parse::{self, FlagConstructor, Lookup, Parser};
+use crate::render::iter;
+use crate::Flag;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+/// Parse flags from CARGO_ENCODED_RUSTDOCFLAGS environment variable.
+///
+/// ```no_run
+/// use rustflags::{Flag, RustdocFlag};
+///
+/// let docsrs = rustflags::from_env_rustdoc().any(|flag| {
+///     matches!(flag, RustdocFlag::Rustc(Flag::Cfg { name, value: None }) if name == "docsrs")
+/// });
+/// ```
+pub fn from_env_rustdoc() -> RustdocFlags {
+    from_env_rustdoc_with(ProcessEnv)
+}
+
+/// Parse flags from CARGO_ENCODED_RUSTDOCFLAGS as provided by `env`.
+#[allow(clippy::needless_pass_by_value)]
+pub fn from_env_rustdoc_with(env: impl EnvSource) -> RustdocFlags {
+    let encoded = env.var_os("CARGO_ENCODED_RUSTDOCFLAGS").unwrap_or_default();
+    RustdocFlags {
+        parser: Parser::new(encoded),
+    }
+}
+
+/// Parse rustdoc flags from a string separated with ASCII unit separator
+/// ('\x1f').
+pub fn from_encoded_rustdoc(encoded: &OsStr) -> RustdocFlags {
+    RustdocFlags {
+        parser: Parser::new(encoded.to_owned()),
+    }
+}
+
+/// **Iterator of rustdoc flags**
+pub struct RustdocFlags {
+    parser: Parser<RustdocFlag>,
+}
+
+impl Iterator for RustdocFlags {
+    type Item = RustdocFlag;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        parse::parse(&mut self.parser)
+    }
+}
+
+/// **One flag recognized by rustdoc**
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RustdocFlag {
+    /// A flag that rustdoc accepts with the same meaning as rustc, such as
+    /// `--cfg docsrs` or `-Zunstable-options`.
+    Rustc(Flag),
+
+    /// `--document-private-items`
+    ///
+    /// Document private items.
+    DocumentPrivateItems,
+
+    /// `--document-hidden-items`
+    ///
+    /// Document items that have doc(hidden).
+    DocumentHiddenItems,
+
+    /// `--html-in-header FILES`
+    ///
+    /// Files to include inline in the `<head>` section of a rendered Markdown
+    /// file or generated documentation.
+    HtmlInHeader(PathBuf),
+
+    /// `--html-before-content FILES`
+    ///
+    /// Files to include inline between `<body>` and the content of a rendered
+    /// Markdown file or generated documentation.
+    HtmlBeforeContent(PathBuf),
+
+    /// `--html-after-content FILES`
+    ///
+    /// Files to include inline between the content and `</body>` of a
+    /// rendered Markdown file or generated documentation.
+    HtmlAfterContent(PathBuf),
+
+    /// `--extend-css PATH`
+    ///
+    /// To add some CSS rules with a given file to generate doc with your own
+    /// theme.
+    ExtendCss(PathBuf),
+
+    /// `--theme FILES`
+    ///
+    /// Additional themes which will be added to the generated docs.
+    Theme(PathBuf),
+
+    /// `--default-theme THEME`
+    ///
+    /// Set the default theme.
+    DefaultTheme(String),
+
+    /// `--default-setting SETTING[=VALUE]`
+    ///
+    /// Default value for a rustdoc setting.
+    DefaultSetting { name: String, value: Option<String> },
+
+    /// `--generate-link-to-definition`
+    ///
+    /// Make the identifiers in the HTML source code pages navigable.
+    GenerateLinkToDefinition,
+
+    /// `--extern-html-root-url NAME=URL`
+    ///
+    /// Base URL to use for dependencies.
+    ExternHtmlRootUrl { name: String, url: String },
+
+    /// `--extern-html-root-takes-precedence`
+    ///
+    /// Give precedence to `--extern-html-root-url`, not `html_root_url`.
+    ExternHtmlRootTakesPrecedence,
+
+    /// `--crate-version VERSION`
+    ///
+    /// Crate version to print into documentation.
+    CrateVersion(String),
+
+    /// `--resource-suffix PATH`
+    ///
+    /// Suffix to add to CSS and JavaScript files.
+    ResourceSuffix(String),
+
+    /// `--enable-index-page`
+    ///
+    /// Generate a crate index page.
+    EnableIndexPage,
+
+    /// `--index-page PATH`
+    ///
+    /// Markdown file to be used as index page.
+    IndexPage(PathBuf),
+
+    /// `--static-root-path PATH`
+    ///
+    /// Path string to force loading static files from in output pages.
+    StaticRootPath(String),
+
+    /// `--show-type-layout`
+    ///
+    /// Include the memory layout of types in the docs.
+    ShowTypeLayout,
+
+    /// `--output-format FORMAT`
+    ///
+    /// The output format for the generated docs, `html` or `json`.
+    OutputFormat(String),
+}
+
+impl From<Flag> for RustdocFlag {
+    fn from(flag: Flag) -> Self {
+        RustdocFlag::Rustc(flag)
+    }
+}
+
+impl Lookup for RustdocFlag {
+    fn lookup_short(ch: char) -> FlagConstructor<Self> {
+        match ch {
+            'o' => FlagConstructor::Opt(opt::out_dir),
+            _ => parse::lookup_short(ch),
+        }
+    }
+
+    fn lookup_long(name: &str) -> FlagConstructor<Self> {
+        match name {
+            "document-private-items" => FlagConstructor::Flag(RustdocFlag::DocumentPrivateItems),
+            "document-hidden-items" => FlagConstructor::Flag(RustdocFlag::DocumentHiddenItems),
+            "html-in-header" => FlagConstructor::Opt(opt::html_in_header),
+            "html-before-content" => FlagConstructor::Opt(opt::html_before_content),
+            "html-after-content" => FlagConstructor::Opt(opt::html_after_content),
+            "extend-css" => FlagConstructor::Opt(opt::extend_css),
+            "theme" => FlagConstructor::Opt(opt::theme),
+            "default-theme" => FlagConstructor::Opt(opt::default_theme),
+            "default-setting" => FlagConstructor::Opt(opt::default_setting),
+            "generate-link-to-definition" => {
+                FlagConstructor::Flag(RustdocFlag::GenerateLinkToDefinition)
+            }
+            "extern-html-root-url" => FlagConstructor::Opt(opt::extern_html_root_url),
+            "extern-html-root-takes-precedence" => {
+                FlagConstructor::Flag(RustdocFlag::ExternHtmlRootTakesPrecedence)
+            }
+            "crate-version" => FlagConstructor::Opt(opt::crate_version),
+            "resource-suffix" => FlagConstructor::Opt(opt::resource_suffix),
+            "enable-index-page" => FlagConstructor::Flag(RustdocFlag::EnableIndexPage),
+            "index-page" => FlagConstructor::Opt(opt::index_page),
+            "static-root-path" => FlagConstructor::Opt(opt::static_root_path),
+            "show-type-layout" => FlagConstructor::Flag(RustdocFlag::ShowTypeLayout),
+            "output-format" => FlagConstructor::Opt(opt::output_format),
+            "output" => FlagConstructor::Opt(opt::out_dir),
+            _ => parse::lookup_long(name),
+        }
+    }
+}
+
+mod opt {
+    use super::RustdocFlag;
+    use crate::string::EnvStr;
+    use crate::Flag;
+    use std::path::PathBuf;
+
+    pub(crate) fn out_dir(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::Rustc(Flag::OutDir(PathBuf::from(arg))))
+    }
+
+    pub(crate) fn html_in_header(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::HtmlInHeader(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn html_before_content(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::HtmlBeforeContent(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn html_after_content(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::HtmlAfterContent(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn extend_css(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::ExtendCss(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn theme(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::Theme(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn default_theme(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        Some(RustdocFlag::DefaultTheme(arg.to_owned()))
+    }
+
+    pub(crate) fn default_setting(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg, None),
+        };
+        let name = name.to_owned();
+        let value = value.map(str::to_owned);
+        Some(RustdocFlag::DefaultSetting { name, value })
+    }
+
+    pub(crate) fn extern_html_root_url(arg: &EnvStr) -> Option<RustdocFlag> {
+        let (name, url) = arg.to_str()?.split_once('=')?;
+        let name = name.to_owned();
+        let url = url.to_owned();
+        Some(RustdocFlag::ExternHtmlRootUrl { name, url })
+    }
+
+    pub(crate) fn crate_version(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        Some(RustdocFlag::CrateVersion(arg.to_owned()))
+    }
+
+    pub(crate) fn resource_suffix(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        Some(RustdocFlag::ResourceSuffix(arg.to_owned()))
+    }
+
+    pub(crate) fn index_page(arg: &EnvStr) -> Option<RustdocFlag> {
+        Some(RustdocFlag::IndexPage(PathBuf::from(arg)))
+    }
+
+    pub(crate) fn static_root_path(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        Some(RustdocFlag::StaticRootPath(arg.to_owned()))
+    }
+
+    pub(crate) fn output_format(arg: &EnvStr) -> Option<RustdocFlag> {
+        let arg = arg.to_str()?;
+        Some(RustdocFlag::OutputFormat(arg.to_owned()))
+    }
+}
+
+impl IntoIterator for RustdocFlag {
+    type Item = OsString;
+    type IntoIter = iter::Iter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut flags = Vec::new();
+
+        match self {
+            RustdocFlag::Rustc(flag) => return flag.into_iter(),
+
+            RustdocFlag::DocumentPrivateItems => {
+                flags.push(OsString::from("--document-private-items"));
+            }
+
+            RustdocFlag::DocumentHiddenItems => {
+                flags.push(OsString::from("--document-hidden-items"));
+            }
+
+            RustdocFlag::HtmlInHeader(path) => {
+                flags.push(OsString::from("--html-in-header"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::HtmlBeforeContent(path) => {
+                flags.push(OsString::from("--html-before-content"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::HtmlAfterContent(path) => {
+                flags.push(OsString::from("--html-after-content"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::ExtendCss(path) => {
+                flags.push(OsString::from("--extend-css"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::Theme(path) => {
+                flags.push(OsString::from("--theme"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::DefaultTheme(theme) => {
+                flags.push(OsString::from("--default-theme"));
+                flags.push(OsString::from(theme));
+            }
+
+            RustdocFlag::DefaultSetting { name, value } => {
+                flags.push(OsString::from("--default-setting"));
+                if let Some(value) = value {
+                    flags.push(OsString::from(format!("{}={}", name, value)));
+                } else {
+                    flags.push(OsString::from(name));
+                }
+            }
+
+            RustdocFlag::GenerateLinkToDefinition => {
+                flags.push(OsString::from("--generate-link-to-definition"));
+            }
+
+            RustdocFlag::ExternHtmlRootUrl { name, url } => {
+                flags.push(OsString::from("--extern-html-root-url"));
+                flags.push(OsString::from(format!("{}={}", name, url)));
+            }
+
+            RustdocFlag::ExternHtmlRootTakesPrecedence => {
+                flags.push(OsString::from("--extern-html-root-takes-precedence"));
+            }
+
+            RustdocFlag::CrateVersion(version) => {
+                flags.push(OsString::from("--crate-version"));
+                flags.push(OsString::from(version));
+            }
+
+            RustdocFlag::ResourceSuffix(suffix) => {
+                flags.push(OsString::from("--resource-suffix"));
+                flags.push(OsString::from(suffix));
+            }
+
+            RustdocFlag::EnableIndexPage => {
+                flags.push(OsString::from("--enable-index-page"));
+            }
+
+            RustdocFlag::IndexPage(path) => {
+                flags.push(OsString::from("--index-page"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::StaticRootPath(path) => {
+                flags.push(OsString::from("--static-root-path"));
+                flags.push(OsString::from(path));
+            }
+
+            RustdocFlag::ShowTypeLayout => {
+                flags.push(OsString::from("--show-type-layout"));
+            }
+
+            RustdocFlag::OutputFormat(format) => {
+                flags.push(OsString::from("--output-format"));
+                flags.push(OsString::from(format));
+            }
+        }
+
+        iter::Iter {
+            items: flags.into_iter(),
+        }
+    }
+}
+
+impl Display for RustdocFlag {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (i, arg) in self.clone().into_iter().enumerate() {
+            if i > 0 {
+                formatter.write_str(" ")?;
+            }
+            Display::fmt(&arg.to_string_lossy(), formatter)?;
+        }
+        Ok(())
+    }
+}
@@ -23,7 +23,7 @@ impl Deref for EnvString {
 }
 
 impl EnvStr {
-    fn new(encoded: &OsStr) -> &Self {
+    pub fn new(encoded: &OsStr) -> &Self {
         unsafe { &*(encoded as *const OsStr as *const EnvStr) }
     }
 
@@ -142,6 +142,20 @@ impl Default for &EnvStr {
     }
 }
 
+// Arguments of a string encoded like CARGO_ENCODED_RUSTFLAGS.
+pub(crate) fn split_encoded(encoded: &OsStr) -> Vec<OsString> {
+    let mut args = Vec::new();
+    let mut rest = EnvStr::new(encoded);
+    while !rest.is_empty() {
+        let (arg, next) = rest
+            .split_once('\x1F')
+            .unwrap_or((rest, Default::default()));
+        args.push(arg.as_ref().to_owned());
+        rest = next;
+    }
+    args
+}
+
 pub(crate) enum EnvChar {
     Valid(char),
     Invalid,
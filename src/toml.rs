@@ -0,0 +1,477 @@
+// Parser for the subset of TOML found in Cargo config files and manifests:
+// tables, arrays of tables, dotted keys, strings of every kind, integers,
+// floats, booleans, arrays and inline tables. Date-times are not supported.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+pub(crate) type Table = BTreeMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub(crate) fn parse(input: &str) -> Result<Table, ParseError> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+    };
+    parser.document().map_err(|message| ParseError {
+        line: input.as_bytes()[..parser.pos.min(input.len())]
+            .split(|&b| b == b'\n')
+            .count(),
+        message,
+    })
+}
+
+// Parse a single value, as found for example in the `--config KEY=VALUE`
+// argument of Cargo or in environment variables holding TOML arrays.
+pub(crate) fn parse_value(input: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+    };
+    let result = parser.value().and_then(|value| {
+        parser.skip_whitespace();
+        if parser.pos == parser.input.len() {
+            Ok(value)
+        } else {
+            Err("unexpected trailing characters".to_owned())
+        }
+    });
+    result.map_err(|message| ParseError { line: 1, message })
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+type Result<T, E = String> = std::result::Result<T, E>;
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(format!("expected `{}`", byte as char))
+        }
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.input[self.pos..].starts_with(prefix.as_bytes())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            while let Some(byte) = self.peek() {
+                if byte == b'\n' {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+    }
+
+    // Whitespace, newlines and comments, as allowed inside of arrays.
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            match self.peek() {
+                Some(b'\n') => self.pos += 1,
+                Some(b'\r') if self.input.get(self.pos + 1) == Some(&b'\n') => self.pos += 2,
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        self.skip_comment();
+        self.eat(b'\r');
+        if self.eat(b'\n') || self.pos == self.input.len() {
+            Ok(())
+        } else {
+            Err("expected newline".to_owned())
+        }
+    }
+
+    fn document(&mut self) -> Result<Table> {
+        let mut root = Table::new();
+        let mut current: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None => return Ok(root),
+                Some(b'[') => {
+                    self.pos += 1;
+                    let array = self.eat(b'[');
+                    self.skip_whitespace();
+                    let path = self.key()?;
+                    self.skip_whitespace();
+                    self.expect(b']')?;
+                    if array {
+                        self.expect(b']')?;
+                    }
+                    self.end_of_line()?;
+                    if array {
+                        let (last, parents) = path.split_last().unwrap();
+                        let parent = descend(&mut root, parents)?;
+                        let entry = parent
+                            .entry(last.clone())
+                            .or_insert_with(|| Value::Array(Vec::new()));
+                        match entry {
+                            Value::Array(array) => array.push(Value::Table(Table::new())),
+                            _ => return Err(format!("duplicate key `{}`", last)),
+                        }
+                    } else {
+                        descend(&mut root, &path)?;
+                    }
+                    current = path;
+                }
+                Some(_) => {
+                    let path = self.key()?;
+                    self.skip_whitespace();
+                    self.expect(b'=')?;
+                    self.skip_whitespace();
+                    let value = self.value()?;
+                    self.end_of_line()?;
+                    let table = descend(&mut root, &current)?;
+                    insert(table, &path, value)?;
+                }
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let segment = match self.peek() {
+                Some(b'"') => self.basic_string()?,
+                Some(b'\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while let Some(b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-') =
+                        self.peek()
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err("expected key".to_owned());
+                    }
+                    String::from_utf8(self.input[start..self.pos].to_vec()).unwrap()
+                }
+            };
+            path.push(segment);
+            self.skip_whitespace();
+            if !self.eat(b'.') {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some(b'"') if self.starts_with("\"\"\"") => {
+                self.multiline_basic_string().map(Value::String)
+            }
+            Some(b'"') => self.basic_string().map(Value::String),
+            Some(b'\'') if self.starts_with("'''") => {
+                self.multiline_literal_string().map(Value::String)
+            }
+            Some(b'\'') => self.literal_string().map(Value::String),
+            Some(b'[') => self.array(),
+            Some(b'{') => self.inline_table(),
+            Some(b't') if self.starts_with("true") => {
+                self.pos += 4;
+                Ok(Value::Boolean(true))
+            }
+            Some(b'f') if self.starts_with("false") => {
+                self.pos += 5;
+                Ok(Value::Boolean(false))
+            }
+            Some(b'+' | b'-' | b'0'..=b'9' | b'i' | b'n') => self.number(),
+            _ => Err("expected value".to_owned()),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.expect(b'[')?;
+        let mut array = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.eat(b']') {
+                return Ok(Value::Array(array));
+            }
+            array.push(self.value()?);
+            self.skip_trivia();
+            if !self.eat(b',') {
+                self.skip_trivia();
+                self.expect(b']')?;
+                return Ok(Value::Array(array));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value> {
+        self.expect(b'{')?;
+        let mut table = Table::new();
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(Value::Table(table));
+        }
+        loop {
+            let path = self.key()?;
+            self.skip_whitespace();
+            self.expect(b'=')?;
+            self.skip_whitespace();
+            let value = self.value()?;
+            insert(&mut table, &path, value)?;
+            self.skip_whitespace();
+            if self.eat(b'}') {
+                return Ok(Value::Table(table));
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'-' | b'.' | b'_') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let text: String = self.input[start..self.pos]
+            .iter()
+            .filter(|&&b| b != b'_')
+            .map(|&b| b as char)
+            .collect();
+        let unsigned = text.trim_start_matches(['+', '-']);
+        let negative = text.starts_with('-');
+        let radix = match unsigned.get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            if let Ok(int) = i64::from_str_radix(&unsigned[2..], radix) {
+                return Ok(Value::Integer(if negative { -int } else { int }));
+            }
+        } else if let Ok(int) = text.parse::<i64>() {
+            return Ok(Value::Integer(int));
+        } else if let Ok(float) = text.parse::<f64>() {
+            return Ok(Value::Float(float));
+        }
+        Err(format!("invalid number `{}`", text))
+    }
+
+    fn basic_string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut string = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err("unterminated string".to_owned()),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(string).map_err(|_| "invalid UTF-8".to_owned());
+                }
+                Some(b'\\') => self.escape(&mut string)?,
+                Some(byte) => {
+                    string.push(byte);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn multiline_basic_string(&mut self) -> Result<String> {
+        self.pos += 3;
+        self.eat(b'\r');
+        self.eat(b'\n');
+        let mut string = Vec::new();
+        loop {
+            if self.starts_with("\"\"\"") && !self.input[self.pos + 3..].starts_with(b"\"") {
+                self.pos += 3;
+                return String::from_utf8(string).map_err(|_| "invalid UTF-8".to_owned());
+            }
+            match self.peek() {
+                None => return Err("unterminated string".to_owned()),
+                Some(b'\\') => {
+                    let rest = &self.input[self.pos + 1..];
+                    let trimmed = rest
+                        .iter()
+                        .position(|b| !matches!(b, b' ' | b'\t' | b'\r'))
+                        .map(|i| &rest[i..]);
+                    if let Some([b'\n', ..]) = trimmed {
+                        // Line ending backslash trims the following whitespace
+                        // and newlines, but not comments.
+                        self.pos += 1;
+                        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.peek() {
+                            self.pos += 1;
+                        }
+                    } else {
+                        self.escape(&mut string)?;
+                    }
+                }
+                Some(byte) => {
+                    string.push(byte);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self, string: &mut Vec<u8>) -> Result<()> {
+        self.pos += 1;
+        let byte = self.peek().ok_or("unterminated string")?;
+        self.pos += 1;
+        let ch = match byte {
+            b'b' => '\u{8}',
+            b't' => '\t',
+            b'n' => '\n',
+            b'f' => '\u{c}',
+            b'r' => '\r',
+            b'e' => '\u{1b}',
+            b'"' => '"',
+            b'\\' => '\\',
+            b'u' | b'U' => {
+                let len = if byte == b'u' { 4 } else { 8 };
+                let hex = self
+                    .input
+                    .get(self.pos..self.pos + len)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .ok_or("invalid unicode escape")?;
+                self.pos += len;
+                u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or("invalid unicode escape")?
+            }
+            _ => return Err(format!("invalid escape `\\{}`", byte as char)),
+        };
+        let mut buf = [0; 4];
+        string.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        Ok(())
+    }
+
+    fn literal_string(&mut self) -> Result<String> {
+        self.expect(b'\'')?;
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err("unterminated string".to_owned()),
+                Some(b'\'') => {
+                    let string = self.input[start..self.pos].to_vec();
+                    self.pos += 1;
+                    return String::from_utf8(string).map_err(|_| "invalid UTF-8".to_owned());
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn multiline_literal_string(&mut self) -> Result<String> {
+        self.pos += 3;
+        self.eat(b'\r');
+        self.eat(b'\n');
+        let start = self.pos;
+        loop {
+            if self.starts_with("'''") && !self.input[self.pos + 3..].starts_with(b"'") {
+                let string = self.input[start..self.pos].to_vec();
+                self.pos += 3;
+                return String::from_utf8(string).map_err(|_| "invalid UTF-8".to_owned());
+            }
+            if self.peek().is_none() {
+                return Err("unterminated string".to_owned());
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+fn descend<'t>(mut table: &'t mut Table, path: &[String]) -> Result<&'t mut Table> {
+    for key in path {
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        table = match entry {
+            Value::Table(table) => table,
+            // Headers following [[array]] refer to its last element.
+            Value::Array(array) => match array.last_mut() {
+                Some(Value::Table(table)) => table,
+                _ => return Err(format!("duplicate key `{}`", key)),
+            },
+            _ => return Err(format!("duplicate key `{}`", key)),
+        };
+    }
+    Ok(table)
+}
+
+fn insert(table: &mut Table, path: &[String], value: Value) -> Result<()> {
+    let (last, parents) = path.split_last().unwrap();
+    let table = descend(table, parents)?;
+    if table.contains_key(last) {
+        return Err(format!("duplicate key `{}`", last));
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
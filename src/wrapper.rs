@@ -0,0 +1,268 @@
+use crate::error::{Error, ErrorKind};
+use crate::parse::{self, FlagConstructor};
+use crate::Flag;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// **Command line of a rustc invocation, as seen by a `RUSTC_WRAPPER`**
+///
+/// Cargo runs `RUSTC_WRAPPER` and `RUSTC_WORKSPACE_WRAPPER` programs with the
+/// path of the real rustc as the first argument, followed by the arguments
+/// for rustc. This type parses those arguments into flags, lets the wrapper
+/// add, remove or rewrite flags, and runs rustc. Arguments that are not
+/// touched, including inputs and flags this crate does not recognize, are
+/// passed on exactly as they were given.
+///
+/// ```no_run
+/// // A RUSTC_WRAPPER that keeps frame pointers and never optimizes for the
+/// // build machine's CPU.
+///
+/// use rustflags::{Flag, RustcInvocation};
+///
+/// fn main() {
+///     let mut rustc = RustcInvocation::from_args_os().unwrap();
+///     rustc.retain(|flag| {
+///         !matches!(flag, Flag::Codegen { opt, value: Some(value) }
+///             if opt == "target-cpu" && value == "native")
+///     });
+///     rustc.push(Flag::Codegen {
+///         opt: "force-frame-pointers".to_owned(),
+///         value: Some("yes".to_owned()),
+///     });
+///     let error = rustc.exec();
+///     panic!("{}", error);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RustcInvocation {
+    rustc: PathBuf,
+    args: Vec<Arg>,
+}
+
+// One or two consecutive arguments, along with the flags parsed from them.
+#[derive(Clone, Debug)]
+pub(crate) struct Arg {
+    pub(crate) tokens: Vec<OsString>,
+    pub(crate) flags: Vec<Flag>,
+    pub(crate) edited: bool,
+}
+
+impl RustcInvocation {
+    /// Parse the arguments of the current process, skipping the name of the
+    /// wrapper program itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no arguments, not even the path of
+    /// rustc.
+    pub fn from_args_os() -> Result<Self, Error> {
+        RustcInvocation::parse(env::args_os().skip(1))
+    }
+
+    /// Parse a rustc path followed by rustc's arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `args` is empty, so there is no path of rustc.
+    pub fn parse<I>(args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let rustc = args.next().ok_or_else(|| Error::new(ErrorKind::NoRustc))?;
+        let tokens: Vec<OsString> = args.collect();
+        Ok(RustcInvocation {
+            rustc: PathBuf::from(rustc),
+            args: group(&tokens),
+        })
+    }
+
+    /// Replace `@path` and `@shell:path` arguments by the contents of those
+    /// files, which are then parsed like the other arguments. See
+    /// [`expand_argfiles`][crate::expand_argfiles].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an argument file cannot be expanded.
+    pub fn expand_argfiles(&mut self) -> Result<&mut Self, Error> {
+        let args = crate::expand_argfiles(self.args())?;
+        *self =
+            RustcInvocation::parse(iter::once(self.rustc.clone().into_os_string()).chain(args))?;
+        Ok(self)
+    }
+
+    /// Path of the rustc to run.
+    pub fn rustc(&self) -> &Path {
+        &self.rustc
+    }
+
+    /// The recognized flags, in order.
+    pub fn flags(&self) -> impl Iterator<Item = &Flag> {
+        self.args.iter().flat_map(|arg| &arg.flags)
+    }
+
+    /// Arguments that are not flags, such as the source file being compiled,
+    /// or `-` for standard input.
+    pub fn inputs(&self) -> impl Iterator<Item = &OsStr> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg.tokens.as_slice() {
+                [token] if arg.flags.is_empty() && !arg.edited && is_input(token) => Some(&**token),
+                _ => None,
+            })
+    }
+
+    /// Remove the flags for which `f` returns false.
+    pub fn retain(&mut self, mut f: impl FnMut(&Flag) -> bool) -> &mut Self {
+        for arg in &mut self.args {
+            let len = arg.flags.len();
+            arg.flags.retain(&mut f);
+            if arg.flags.len() != len {
+                arg.edited = true;
+            }
+        }
+        self.args
+            .retain(|arg| !(arg.edited && arg.flags.is_empty()));
+        self
+    }
+
+    /// Rewrite each flag.
+    pub fn map(&mut self, mut f: impl FnMut(Flag) -> Flag) -> &mut Self {
+        for arg in &mut self.args {
+            for flag in &mut arg.flags {
+                let rewritten = f(flag.clone());
+                if rewritten != *flag {
+                    *flag = rewritten;
+                    arg.edited = true;
+                }
+            }
+        }
+        self
+    }
+
+    /// Add a flag after the existing arguments.
+    pub fn push(&mut self, flag: Flag) -> &mut Self {
+        self.args.push(Arg {
+            tokens: Vec::new(),
+            flags: vec![flag],
+            edited: true,
+        });
+        self
+    }
+
+    /// Arguments for rustc, with the original tokens of everything that was
+    /// not edited.
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        for arg in &self.args {
+            if arg.edited {
+                args.extend(arg.flags.iter().cloned().flatten());
+            } else {
+                args.extend(arg.tokens.iter().cloned());
+            }
+        }
+        args
+    }
+
+    /// Command running rustc with [`args`][Self::args].
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.rustc);
+        cmd.args(self.args());
+        cmd
+    }
+
+    /// Run rustc in place of the current process.
+    ///
+    /// On Unix the current process is replaced by rustc. Elsewhere rustc runs
+    /// as a child process, and the current process exits with its exit code.
+    /// This only returns if rustc could not be started.
+    pub fn exec(&self) -> Error {
+        let error = exec(self.command());
+        Error::new(ErrorKind::Exec {
+            path: self.rustc.clone(),
+            error,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn exec(mut cmd: Command) -> std::io::Error {
+    use std::os::unix::process::CommandExt as _;
+    cmd.exec()
+}
+
+#[cfg(not(unix))]
+fn exec(mut cmd: Command) -> std::io::Error {
+    match cmd.status() {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(error) => error,
+    }
+}
+
+// Split arguments into the groups that make up one flag each, parsing the
+// flags of each group. A group that is not a recognized flag has no flags.
+pub(crate) fn group(tokens: &[OsString]) -> Vec<Arg> {
+    let mut args = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let len = token_count(&tokens[i]).min(tokens.len() - i);
+        let tokens = tokens[i..i + len].to_vec();
+        let flags = if tokens
+            .iter()
+            .any(|token| token.as_encoded_bytes().contains(&b'\x1F'))
+        {
+            Vec::new()
+        } else {
+            crate::from_encoded(&tokens.join(OsStr::new("\x1F"))).collect()
+        };
+        args.push(Arg {
+            tokens,
+            flags,
+            edited: false,
+        });
+        i += len;
+    }
+    args
+}
+
+// Number of arguments, starting with `token`, that make up one flag: 2 for a
+// flag whose value is in the next argument, like `--cfg` `unix` or `-C`
+// `opt-level=3`, and 1 otherwise.
+fn token_count(token: &OsStr) -> usize {
+    let Some(token) = token.to_str() else {
+        return 1;
+    };
+    if let Some(long) = token.strip_prefix("--") {
+        if long.is_empty() || long.contains('=') {
+            return 1;
+        }
+        return match parse::lookup_long::<Flag>(long) {
+            FlagConstructor::Opt(_) | FlagConstructor::Repeated(_) => 2,
+            FlagConstructor::Flag(_) | FlagConstructor::Unrecognized => 1,
+        };
+    }
+    let Some(short) = token.strip_prefix('-') else {
+        return 1;
+    };
+    // Short flags without a value may be combined, as in `-vV`. A short flag
+    // with a value takes the rest of the argument, or else the next one.
+    let mut chars = short.chars();
+    while let Some(ch) = chars.next() {
+        match parse::lookup_short::<Flag>(ch) {
+            FlagConstructor::Flag(_) => {}
+            FlagConstructor::Opt(_) | FlagConstructor::Repeated(_) => {
+                return if chars.as_str().is_empty() { 2 } else { 1 };
+            }
+            FlagConstructor::Unrecognized => return 1,
+        }
+    }
+    1
+}
+
+fn is_input(token: &OsStr) -> bool {
+    token == "-" || !token.as_encoded_bytes().starts_with(b"-")
+}
@@ -0,0 +1,15 @@
+// Helpers shared by the integration tests. Not every test crate uses all of
+// them on every platform.
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// Empty directory for one test, unique to the current test process.
+pub fn tempdir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("rustflags-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
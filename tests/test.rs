@@ -80,6 +80,12 @@ fn test_individual() {
         },
     );
 
+    // Flag::CheckCfg
+    assert_flags!(
+        "--check-cfg" "cfg(feature, values(\"std\"))",
+        Flag::CheckCfg("cfg(feature, values(\"std\"))".to_owned()),
+    );
+
     // Flag::LibrarySearchPath
     assert_flags!(
         "-L" "PATH",
@@ -0,0 +1,141 @@
+mod common;
+
+use rustflags::{Flag, RustcInvocation};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+fn at(prefix: &str, path: &Path) -> String {
+    format!("@{}{}", prefix, path.display())
+}
+
+#[test]
+fn test_lines() {
+    let dir = common::tempdir("argfile-lines");
+    let argfile = dir.join("args");
+    fs::write(&argfile, "--cfg\nfeature=\"has space\"\r\n-Copt-level=3\n").unwrap();
+
+    let args =
+        rustflags::expand_argfiles(["src/lib.rs".to_owned(), at("", &argfile), "-g".to_owned()])
+            .unwrap();
+    assert_eq!(
+        args,
+        [
+            "src/lib.rs",
+            "--cfg",
+            "feature=\"has space\"",
+            "-Copt-level=3",
+            "-g"
+        ]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_shell() {
+    let dir = common::tempdir("argfile-shell");
+    let argfile = dir.join("args");
+    fs::write(
+        &argfile,
+        "# comment\n--cfg 'feature=\"std\"' -C \"link-arg=-Wl,-rpath,\\$ORIGIN\" \\\n  -L dir\\ with\\ spaces ''\n",
+    )
+    .unwrap();
+
+    let args = rustflags::expand_argfiles([at("shell:", &argfile)]).unwrap();
+    assert_eq!(
+        args,
+        [
+            "--cfg",
+            "feature=\"std\"",
+            "-C",
+            "link-arg=-Wl,-rpath,$ORIGIN",
+            "-L",
+            "dir with spaces",
+            "",
+        ],
+    );
+
+    fs::write(&argfile, "--cfg 'unterminated\n").unwrap();
+    let error = rustflags::expand_argfiles([at("shell:", &argfile)]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "invalid argument file {}: unterminated single quote",
+            argfile.display()
+        ),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_nested() {
+    let dir = common::tempdir("argfile-nested");
+    let outer = dir.join("outer");
+    let inner = dir.join("inner");
+    fs::write(
+        &outer,
+        format!("-O\n{}\n{}\n", at("", &inner), at("", &inner)),
+    )
+    .unwrap();
+    fs::write(&inner, "--cfg\nunix\n").unwrap();
+
+    let args = rustflags::expand_argfiles([at("", &outer)]).unwrap();
+    assert_eq!(args, ["-O", "--cfg", "unix", "--cfg", "unix"]);
+
+    // inner -> outer -> inner
+    fs::write(&inner, format!("--cfg\nunix\n{}\n", at("", &outer))).unwrap();
+    let error = rustflags::expand_argfiles([at("", &outer)]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "invalid argument file {}: argument file includes itself",
+            outer.display()
+        ),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_missing() {
+    let missing = env::temp_dir().join("rustflags-argfile-missing");
+    let error = rustflags::expand_argfiles([at("", &missing)]).unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with(&format!("failed to read {}: ", missing.display())));
+}
+
+#[test]
+fn test_invocation() {
+    let dir = common::tempdir("argfile-invocation");
+    let argfile = dir.join("args");
+    fs::write(&argfile, "-C\ntarget-cpu=native\n--edition=2021\n").unwrap();
+
+    let args = [
+        OsString::from("rustc"),
+        OsString::from(at("", &argfile)),
+        OsString::from("main.rs"),
+    ];
+    let mut rustc = RustcInvocation::parse(args).unwrap();
+    assert_eq!(rustc.flags().count(), 0);
+    rustc.expand_argfiles().unwrap();
+    assert_eq!(
+        rustc.flags().cloned().collect::<Vec<_>>(),
+        [
+            Flag::Codegen {
+                opt: "target-cpu".to_owned(),
+                value: Some("native".to_owned()),
+            },
+            Flag::Edition(2021),
+        ],
+    );
+    assert_eq!(
+        rustc.args(),
+        ["-C", "target-cpu=native", "--edition=2021", "main.rs"]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,149 @@
+use rustflags::{Flag, LibraryKind, LinkKind, LinkModifier, LinkModifierPrefix};
+use std::path::PathBuf;
+
+#[test]
+fn test_from_build_script_output() {
+    let output = "\
+cargo:rerun-if-changed=build.rs
+cargo:rustc-link-lib=static:+whole-archive=foo
+cargo::rustc-link-search=native=/x
+cargo:rustc-cfg=foo=\"bar\"
+cargo::rustc-cfg=has_foo
+cargo::rustc-check-cfg=cfg(foo, values(\"bar\"))
+cargo:rustc-flags=-l bar -L/y -lstatic=baz
+cargo::rustc-link-arg=-Wl,--gc-sections
+cargo::rustc-link-arg-bins=-Wl,-z,now
+cargo::warning=unrelated
+cargo:rustc-env=FOO=1
+some other output
+";
+
+    assert_eq!(
+        rustflags::from_build_script_output(output),
+        [
+            Flag::Link {
+                kind: LinkKind::Static,
+                modifiers: vec![(LinkModifierPrefix::Enable, LinkModifier::WholeArchive)],
+                name: "foo".to_owned(),
+                rename: None,
+            },
+            Flag::LibrarySearchPath {
+                kind: LibraryKind::Native,
+                path: PathBuf::from("/x"),
+            },
+            Flag::Cfg {
+                name: "foo".to_owned(),
+                value: Some("bar".to_owned()),
+            },
+            Flag::Cfg {
+                name: "has_foo".to_owned(),
+                value: None,
+            },
+            Flag::CheckCfg("cfg(foo, values(\"bar\"))".to_owned()),
+            Flag::Link {
+                kind: LinkKind::Dylib,
+                modifiers: Vec::new(),
+                name: "bar".to_owned(),
+                rename: None,
+            },
+            Flag::LibrarySearchPath {
+                kind: LibraryKind::All,
+                path: PathBuf::from("/y"),
+            },
+            Flag::Link {
+                kind: LinkKind::Static,
+                modifiers: Vec::new(),
+                name: "baz".to_owned(),
+                rename: None,
+            },
+            Flag::Codegen {
+                opt: "link-arg".to_owned(),
+                value: Some("-Wl,--gc-sections".to_owned()),
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_crlf() {
+    assert_eq!(
+        rustflags::from_build_script_output("cargo:rustc-link-lib=z\r\n"),
+        [Flag::Link {
+            kind: LinkKind::Dylib,
+            modifiers: Vec::new(),
+            name: "z".to_owned(),
+            rename: None,
+        }],
+    );
+}
+
+#[test]
+fn test_to_build_script_output() {
+    let flags = [
+        Flag::Link {
+            kind: LinkKind::Static,
+            modifiers: vec![
+                (LinkModifierPrefix::Enable, LinkModifier::WholeArchive),
+                (LinkModifierPrefix::Disable, LinkModifier::Bundle),
+            ],
+            name: "foo".to_owned(),
+            rename: None,
+        },
+        Flag::Link {
+            kind: LinkKind::Dylib,
+            modifiers: Vec::new(),
+            name: "z".to_owned(),
+            rename: None,
+        },
+        Flag::LibrarySearchPath {
+            kind: LibraryKind::Native,
+            path: PathBuf::from("/x"),
+        },
+        Flag::LibrarySearchPath {
+            kind: LibraryKind::All,
+            path: PathBuf::from("/y"),
+        },
+        Flag::Cfg {
+            name: "foo".to_owned(),
+            value: Some("bar".to_owned()),
+        },
+        Flag::CheckCfg("cfg(foo, values(\"bar\"))".to_owned()),
+        Flag::Codegen {
+            opt: "link-args".to_owned(),
+            value: Some("-Wl,-z,now -pthread".to_owned()),
+        },
+    ];
+
+    let output = rustflags::to_build_script_output(&flags).unwrap();
+    assert_eq!(
+        output,
+        "\
+cargo::rustc-link-lib=static:+whole-archive,-bundle=foo
+cargo::rustc-link-lib=z
+cargo::rustc-link-search=native=/x
+cargo::rustc-link-search=/y
+cargo::rustc-cfg=foo=\"bar\"
+cargo::rustc-check-cfg=cfg(foo, values(\"bar\"))
+cargo::rustc-link-arg=-Wl,-z,now
+cargo::rustc-link-arg=-pthread
+",
+    );
+
+    let mut round_trip = rustflags::from_build_script_output(&output);
+    assert_eq!(round_trip.len(), 8);
+    round_trip.truncate(6);
+    assert_eq!(round_trip, flags[..6]);
+}
+
+#[test]
+fn test_no_directive() {
+    let flags = [Flag::Codegen {
+        opt: "opt-level".to_owned(),
+        value: Some("3".to_owned()),
+    }];
+    let error = rustflags::to_build_script_output(&flags).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`-C opt-level=3` has no build script equivalent",
+    );
+}
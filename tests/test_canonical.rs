@@ -0,0 +1,133 @@
+use rustflags::Flag;
+use std::ffi::OsStr;
+
+fn canonicalize(rustflags: &str) -> String {
+    let args: Vec<&str> = rustflags.split_whitespace().collect();
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(&args.join("\x1F"))).collect();
+    let canonical: Vec<String> = rustflags::canonicalize(&flags)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    canonical.join(" ")
+}
+
+#[test]
+fn test_aliases() {
+    assert_eq!(canonicalize("-O -g"), "-C debuginfo=2 -C opt-level=2");
+    assert_eq!(
+        canonicalize("-Copt_level=2 -Cdebuginfo=full -Cprefer-dynamic=on"),
+        "-C debuginfo=2 -C opt-level=2 -C prefer-dynamic=yes",
+    );
+    assert_eq!(
+        canonicalize("-Cprefer-dynamic -Clink-arg=-v"),
+        "-C prefer-dynamic=yes -C link-arg=-v",
+    );
+    assert_eq!(
+        canonicalize("-Wclippy::unwrap-used -Zshare_generics -Zunstable-options=on"),
+        "-Z share-generics=yes -Z unstable-options=yes --warn clippy::unwrap_used",
+    );
+}
+
+#[test]
+fn test_overrides() {
+    assert_eq!(
+        canonicalize("-Copt-level=3 --edition=2018 -O --edition 2021"),
+        "--edition 2021 -C opt-level=2",
+    );
+    assert_eq!(
+        canonicalize("-Zfoo=1 -Zbar -Zfoo=2 -Zbar=no"),
+        "-Z bar=no -Z foo=2",
+    );
+    assert_eq!(
+        canonicalize("-Zcrate-attr=feature(a) -Zcrate_attr=feature(b)"),
+        "-Z crate-attr=feature(a) -Z crate-attr=feature(b)",
+    );
+    assert_eq!(
+        canonicalize("-Dwarnings -Aunused -Wwarnings"),
+        "--allow unused --warn warnings"
+    );
+
+    // Forbid and force-warn cannot be overridden by later flags.
+    assert_eq!(
+        canonicalize("-Funsafe_code -Aunsafe_code"),
+        "--forbid unsafe_code",
+    );
+    assert_eq!(
+        canonicalize("--force-warn deprecated -Adeprecated -Wdeprecated"),
+        "--force-warn deprecated",
+    );
+    assert_ne!(
+        canonicalize("-Funsafe_code -Aunsafe_code"),
+        canonicalize("-Aunsafe_code"),
+    );
+}
+
+#[test]
+fn test_reorder() {
+    let canonical = "--cfg a --cfg b --crate-type lib -C opt-level=3";
+    assert_eq!(
+        canonicalize("--cfg a --cfg b -Copt-level=3 --crate-type=lib"),
+        canonical
+    );
+    assert_eq!(
+        canonicalize("-Copt-level=3 --cfg b --crate-type lib --cfg a --cfg b"),
+        canonical
+    );
+}
+
+#[test]
+fn test_order_sensitive() {
+    assert_eq!(
+        canonicalize("-L /b -lfoo -L /a -lbar -L /b -lfoo"),
+        "-L /b -l foo -L /a -l bar -l foo",
+    );
+    assert_eq!(
+        canonicalize("-Clink-arg=-b --cfg a -Clink-arg=-a -Clink-arg=-b"),
+        "--cfg a -C link-arg=-b -C link-arg=-a -C link-arg=-b",
+    );
+    assert_eq!(
+        canonicalize("-Wclippy::unwrap_used -Aclippy::pedantic"),
+        "--warn clippy::unwrap_used --allow clippy::pedantic",
+    );
+    assert_eq!(
+        canonicalize("-Aclippy::pedantic -Wclippy::unwrap_used"),
+        "--allow clippy::pedantic --warn clippy::unwrap_used",
+    );
+}
+
+fn fingerprint(rustflags: &str) -> u64 {
+    let args: Vec<&str> = rustflags.split_whitespace().collect();
+    rustflags::from_encoded(OsStr::new(&args.join("\x1F"))).fingerprint()
+}
+
+#[test]
+fn test_fingerprint() {
+    let expected = fingerprint("--cfg a -Copt-level=2");
+    assert_eq!(fingerprint("-O --cfg a --cfg a"), expected);
+    assert_eq!(
+        fingerprint(
+            "--color=always --cfg a -Dwarnings -O --error-format=json -v --json=diagnostic-short"
+        ),
+        expected,
+    );
+    assert_ne!(fingerprint("--cfg a -Copt-level=3"), expected);
+    assert_ne!(fingerprint("--cfg a"), expected);
+
+    // Equivalent spellings of the same setting.
+    assert_eq!(
+        fingerprint("-Cprefer-dynamic"),
+        fingerprint("-Cprefer_dynamic=yes"),
+    );
+    assert_eq!(
+        fingerprint("-Zshare-generics=1 -Zshare-generics=no"),
+        fingerprint("-Zshare_generics=off"),
+    );
+    assert_ne!(
+        fingerprint("-l static=a -l static=b"),
+        fingerprint("-l static=b -l static=a"),
+    );
+
+    // Pinned, since fingerprints are stored across builds.
+    assert_eq!(fingerprint(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(expected, 0x3d51_d2be_3bfd_4b3a);
+}
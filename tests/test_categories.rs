@@ -0,0 +1,37 @@
+use rustflags::{Category, Flag};
+use std::ffi::OsStr;
+
+fn categories(encoded: &str) -> Vec<&'static [Category]> {
+    rustflags::from_encoded(OsStr::new(encoded))
+        .map(|flag| flag.categories())
+        .collect()
+}
+
+#[test]
+fn test_categories() {
+    assert_eq!(
+        categories("-O\x1f-Clinker=clang\x1f-Ctarget-feature=+avx2\x1f-Dwarnings\x1f--out-dir=target\x1f--cfg\x1fdocsrs\x1f--print=cfg"),
+        [
+            &[Category::Codegen][..],
+            &[Category::Linking],
+            &[Category::Codegen, Category::Cfg],
+            &[Category::Diagnostics],
+            &[Category::Output],
+            &[Category::Cfg],
+            &[Category::Informational],
+        ],
+    );
+}
+
+#[test]
+fn test_drop_non_codegen() {
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(
+        "--error-format=json\x1f-Copt-level=3\x1f-Wunused\x1f--color=always\x1f-C\x1fpanic=abort",
+    ))
+    .filter(|flag| flag.categories().contains(&Category::Codegen))
+    .collect();
+    assert_eq!(
+        rustflags::to_encoded(flags),
+        "-C\x1fopt-level=3\x1f-C\x1fpanic=abort",
+    );
+}
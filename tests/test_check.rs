@@ -0,0 +1,111 @@
+use rustflags::{Channel, CrateType, Flag, LintLevel, Severity};
+use std::ffi::OsStr;
+
+fn codegen(opt: &str, value: &str) -> Flag {
+    Flag::Codegen {
+        opt: opt.to_owned(),
+        value: Some(value.to_owned()),
+    }
+}
+
+fn check(encoded: &str) -> Vec<String> {
+    rustflags::from_encoded(OsStr::new(encoded))
+        .check(None)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_clean() {
+    assert!(check("-Copt-level=3\x1f--cfg\x1ftokio_unstable\x1f-Dwarnings").is_empty());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        check("-Cprefer-dynamic\x1f-Ctarget-feature=+crt-static"),
+        ["error: `-C prefer-dynamic` cannot be combined with a statically linked C runtime (`-C prefer-dynamic`, `-C target-feature=+crt-static`)"],
+    );
+    assert!(check("-Cprefer-dynamic\x1f-Ctarget-feature=+crt-static,-crt-static").is_empty());
+    assert_eq!(
+        check("-Cprefer_dynamic\x1f-Ctarget_feature=+crt-static"),
+        ["error: `-C prefer-dynamic` cannot be combined with a statically linked C runtime (`-C prefer_dynamic`, `-C target_feature=+crt-static`)"],
+    );
+
+    assert_eq!(
+        check("--target=x86_64-unknown-linux-gnu\x1f--target\x1faarch64-unknown-linux-gnu"),
+        ["error: conflicting `--target` flags (`--target x86_64-unknown-linux-gnu`, `--target aarch64-unknown-linux-gnu`)"],
+    );
+
+    let flags = [codegen("lto", "fat"), codegen("embed-bitcode", "no")];
+    let diagnostics = rustflags::check(&flags, None);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].indices, [1, 0]);
+    assert_eq!(diagnostics[0].flags, [flags[1].clone(), flags[0].clone()]);
+}
+
+#[test]
+fn test_warnings() {
+    let flags = [
+        Flag::Z("sanitizer=address".to_owned()),
+        codegen("target-cpu", "native"),
+        Flag::CrateType(CrateType::ProcMacro),
+        codegen("panic", "abort"),
+        Flag::Deny("warnings".to_owned()),
+        Flag::CapLints(LintLevel::Warn),
+    ];
+    let diagnostics: Vec<(Severity, Vec<usize>)> = rustflags::check(&flags, None)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.indices))
+        .collect();
+    assert_eq!(
+        diagnostics,
+        [
+            (Severity::Warning, vec![2, 3]),
+            (Severity::Warning, vec![1]),
+            (Severity::Warning, vec![5, 4]),
+            (Severity::Note, vec![0]),
+        ],
+    );
+
+    assert_eq!(
+        check("-Cpanic=abort"),
+        ["note: unless Cargo is given `--target`, `-C panic=abort` in rustflags applies to proc macros too (`-C panic=abort`)"],
+    );
+}
+
+#[test]
+fn test_channel() {
+    assert_eq!(
+        Channel::from_version("rustc 1.80.0 (051478957 2024-07-21)"),
+        Some(Channel::Stable),
+    );
+    assert_eq!(
+        Channel::from_version("rustc 1.81.0-beta.3 (4f9cd9b02 2024-07-30)"),
+        Some(Channel::Beta),
+    );
+    assert_eq!(
+        Channel::from_version("rustc 1.82.0-nightly (ada5e2c7b 2024-08-01)"),
+        Some(Channel::Nightly),
+    );
+    assert_eq!(
+        Channel::from_version("rustc 1.82.0-dev"),
+        Some(Channel::Dev)
+    );
+    assert_eq!(Channel::from_version("cargo 1.80.0"), None);
+
+    let flags = [Flag::Z("sanitizer=address".to_owned())];
+    let diagnostics = rustflags::check(&flags, Some(Channel::Stable));
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "error: `-Z` flags are not accepted by a stable toolchain (`-Z sanitizer=address`)",
+    );
+    assert_eq!(
+        rustflags::check(&flags, Some(Channel::Beta))[0].severity,
+        Severity::Error,
+    );
+    assert!(rustflags::check(&flags, Some(Channel::Nightly)).is_empty());
+    assert_eq!(rustflags::check(&flags, None)[0].severity, Severity::Note);
+}
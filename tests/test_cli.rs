@@ -0,0 +1,147 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn rustflags(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rustflags"))
+        .args(args)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("RUSTFLAGS")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_human() {
+    assert_eq!(
+        rustflags(&["-Copt-level=3 --cfg=tokio_unstable"]),
+        "-C opt-level=3\n--cfg tokio_unstable\n",
+    );
+    assert_eq!(
+        rustflags(&["-Dwarnings\x1f-L\x1f/opt/lib"]),
+        "--deny warnings\n-L /opt/lib\n"
+    );
+}
+
+#[test]
+fn test_tokens() {
+    assert_eq!(
+        rustflags(&["--format", "tokens", "-Copt-level=3"]),
+        "-C\nopt-level=3\n",
+    );
+}
+
+#[test]
+fn test_json() {
+    assert_eq!(
+        rustflags(&["--format", "json", "-Copt-level=3 --cfg feature=\"std\""]),
+        "[\n  {\"kind\": \"Codegen\", \"args\": [\"-C\", \"opt-level=3\"]},\n  {\"kind\": \"Cfg\", \"args\": [\"--cfg\", \"feature=\\\"std\\\"\"]}\n]\n",
+    );
+    assert_eq!(rustflags(&["--format", "json", ""]), "[]\n");
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(
+        rustflags(&["encode", "-Copt-level=3", "--cfg tokio_unstable"]),
+        "-Copt-level=3\x1f--cfg\x1ftokio_unstable\n",
+    );
+    assert_eq!(
+        rustflags(&["encode", "-Zfoo --unknown-flag x -Ctarget-cpu=native"]),
+        "-Zfoo\x1f--unknown-flag\x1fx\x1f-Ctarget-cpu=native\n",
+    );
+}
+
+fn check(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_rustflags"))
+        .arg("check")
+        .args(args)
+        .env("RUSTC", "rustflags-test-missing-rustc")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("RUSTFLAGS")
+        .output()
+        .unwrap();
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.code().unwrap(), stderr)
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(
+        check(&["-Copt-level=3 --cfg tokio_unstable"]),
+        (0, String::new())
+    );
+
+    let (code, stderr) = check(&["-Copt-level=3 -C target-cpu=native"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stderr,
+        "\
+warning: `-C target-cpu=native` produces binaries that may not run on other machines
+  --> command line
+   |
+   | -C opt-level=3 -C target-cpu=native
+   |                ^^^^^^^^^^^^^^^^^^^^
+
+",
+    );
+}
+
+#[test]
+fn test_check_policy() {
+    let dir = common::tempdir("cli-policy");
+    let policy = dir.join("policy.toml");
+    fs::write(&policy, "deny = [\"-Zbuild-std\"]\n").unwrap();
+    let file = dir.join("rustflags");
+    fs::write(&file, "-Copt-level=3\x1f-Z\x1fbuild-std=core\n").unwrap();
+
+    let (code, stderr) = check(&[
+        "--policy",
+        policy.to_str().unwrap(),
+        "--file",
+        file.to_str().unwrap(),
+    ]);
+    assert_eq!(code, 1);
+    assert_eq!(
+        stderr.split("\n\n").next().unwrap(),
+        format!(
+            "\
+error: `-Z build-std=core` is denied by policy (`-Zbuild-std`)
+  --> {}
+   |
+   | -C opt-level=3 -Z build-std=core
+   |                ^^^^^^^^^^^^^^^^^",
+            file.display(),
+        ),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_diff() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rustflags"))
+        .args(["diff", "-O --cfg a", "-Copt-level=3 --cfg a"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"~ -C opt-level: 2 -> 3\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rustflags"))
+        .args([
+            "diff",
+            "-O --cfg a --cfg b",
+            "--cfg b -Copt-level=2 --cfg a",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,29 @@
+use rustflags::Flag;
+use std::ffi::OsStr;
+
+fn flags(encoded: &str) -> Vec<Flag> {
+    rustflags::from_encoded(OsStr::new(encoded)).collect()
+}
+
+#[test]
+fn test_llvm_args() {
+    let flags = flags(
+        "-Cllvm-args=-inline-threshold=500  -unroll-count=4\x1f-C\x1fllvm_args=-x86-asm-syntax=intel\x1f-Copt-level=3",
+    );
+    assert_eq!(
+        rustflags::llvm_args(&flags),
+        [
+            "-inline-threshold=500",
+            "-unroll-count=4",
+            "-x86-asm-syntax=intel",
+        ],
+    );
+    assert!(rustflags::passes(&flags).is_empty());
+}
+
+#[test]
+fn test_passes() {
+    let flags = flags("-Cpasses=lint verify\x1f-Cpasses=loop-unroll");
+    assert_eq!(rustflags::passes(&flags), ["lint", "verify", "loop-unroll"]);
+    assert!(rustflags::llvm_args(&flags).is_empty());
+}
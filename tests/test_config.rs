@@ -0,0 +1,367 @@
+mod common;
+
+use rustflags::{Flag, FlagReport, FlagSource, RustFlags};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+struct Vars(BTreeMap<String, OsString>);
+
+impl Vars {
+    fn set(&mut self, key: &str, value: impl Into<OsString>) {
+        self.0.insert(key.to_owned(), value.into());
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+
+    fn cargo_config(&self, cwd: &Path, target: &str) -> Result<RustFlags, rustflags::Error> {
+        rustflags::from_cargo_config_with(cwd, target, |key: &str| self.0.get(key).cloned())
+    }
+
+    fn flag_sources(&self, cwd: &Path, target: &str, cli: &[&str]) -> FlagReport {
+        rustflags::flag_sources_with(cwd, target, cli, |key: &str| self.0.get(key).cloned())
+            .unwrap()
+    }
+
+    fn rustflags(&self, cwd: &Path, target: &str) -> Vec<String> {
+        self.cargo_config(cwd, target)
+            .unwrap()
+            .map(|flag| flag.to_string())
+            .collect()
+    }
+}
+
+fn tempdir(name: &str) -> (PathBuf, Vars) {
+    let dir = common::tempdir(&format!("config-{name}"));
+    let mut vars = Vars::default();
+    vars.set("CARGO_HOME", dir.join("cargo-home"));
+    (dir, vars)
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_build_rustflags() {
+    let (dir, mut vars) = tempdir("build");
+    let project = dir.join("workspace/project");
+    fs::create_dir_all(&project).unwrap();
+
+    write(
+        &dir.join("cargo-home/config.toml"),
+        "[build]\nrustflags = [\"-Ctarget-cpu=native\"]\n",
+    );
+    write(
+        &dir.join("workspace/.cargo/config.toml"),
+        "# workspace config\n[build]\nrustflags = [\n    \"--cfg\", 'tokio_unstable', # trailing comment\n]\n",
+    );
+    write(
+        &project.join(".cargo/config.toml"),
+        "build.rustflags = [\"-Dwarnings\"]\n",
+    );
+
+    assert_eq!(
+        vars.rustflags(&project, "x86_64-unknown-linux-gnu"),
+        [
+            "-C target-cpu=native",
+            "--cfg tokio_unstable",
+            "--deny warnings",
+        ],
+    );
+
+    vars.set("CARGO_BUILD_RUSTFLAGS", "-Cdebuginfo=1");
+    assert_eq!(
+        vars.rustflags(&project, "x86_64-unknown-linux-gnu"),
+        [
+            "-C target-cpu=native",
+            "--cfg tokio_unstable",
+            "--deny warnings",
+            "-C debuginfo=1",
+        ],
+    );
+
+    // A string cannot be merged with an array from another config file.
+    write(
+        &project.join(".cargo/config.toml"),
+        "[build]\nrustflags = \"-Copt-level=1  -Ccodegen-units=1\"\n",
+    );
+    vars.remove("CARGO_BUILD_RUSTFLAGS");
+    let error = vars
+        .cargo_config(&project, "x86_64-unknown-linux-gnu")
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "invalid config in {}: failed to merge `build.rustflags`: expected array, but found string",
+            project.join(".cargo/config.toml").display(),
+        ),
+    );
+
+    // A string replaces lower precedence strings rather than appending.
+    write(
+        &dir.join("cargo-home/config.toml"),
+        "[build]\nrustflags = \"-Ctarget-cpu=native\"\n",
+    );
+    write(
+        &dir.join("workspace/.cargo/config.toml"),
+        "[build]\nrustflags = \"--cfg tokio_unstable\"\n",
+    );
+    assert_eq!(
+        vars.rustflags(&project, "x86_64-unknown-linux-gnu"),
+        ["-C opt-level=1", "-C codegen-units=1"],
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_target_rustflags() {
+    let (dir, mut vars) = tempdir("target");
+
+    write(
+        &dir.join(".cargo/config.toml"),
+        r#"
+[build]
+rustflags = ["--cfg=from_build"]
+
+[target.x86_64-unknown-linux-gnu]
+rustflags = ["--cfg=from_triple"]
+
+[target.'cfg(all(unix, target_arch = "x86_64"))']
+rustflags = ["--cfg=from_cfg"]
+
+[target."cfg(windows)"]
+rustflags = ["--cfg=from_windows"]
+"#,
+    );
+
+    assert_eq!(
+        vars.rustflags(&dir, "x86_64-unknown-linux-gnu"),
+        ["--cfg from_triple", "--cfg from_cfg"],
+    );
+    assert_eq!(
+        vars.rustflags(&dir, "x86_64-pc-windows-msvc"),
+        ["--cfg from_windows"],
+    );
+    assert_eq!(
+        vars.rustflags(&dir, "aarch64-apple-darwin"),
+        ["--cfg from_build"],
+    );
+
+    vars.set(
+        "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS",
+        "[\"--cfg=from_env\"]",
+    );
+    assert_eq!(
+        vars.rustflags(&dir, "x86_64-unknown-linux-gnu"),
+        ["--cfg from_triple", "--cfg from_env", "--cfg from_cfg"],
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_env_precedence() {
+    let (dir, mut vars) = tempdir("env");
+    write(
+        &dir.join(".cargo/config.toml"),
+        "[build]\nrustflags = [\"--cfg=from_config\"]\n",
+    );
+
+    vars.set("RUSTFLAGS", "-C opt-level=3 --cfg from_rustflags");
+    assert_eq!(
+        vars.rustflags(&dir, "x86_64-unknown-linux-gnu"),
+        ["-C opt-level=3", "--cfg from_rustflags"],
+    );
+
+    vars.set("CARGO_ENCODED_RUSTFLAGS", "-Dwarnings");
+    let flags: Vec<Flag> = vars
+        .cargo_config(&dir, "x86_64-unknown-linux-gnu")
+        .unwrap()
+        .collect();
+    assert_eq!(flags, [Flag::Deny("warnings".to_owned())]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_invalid_config() {
+    let (dir, vars) = tempdir("invalid");
+    write(
+        &dir.join(".cargo/config.toml"),
+        "[build]\nrustflags = [\"-Dwarnings\"\n",
+    );
+
+    let error = vars
+        .cargo_config(&dir, "x86_64-unknown-linux-gnu")
+        .err()
+        .unwrap();
+    assert!(error.to_string().starts_with("invalid config in "));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_flag_sources() {
+    let (dir, mut vars) = tempdir("sources");
+    let config = dir.join(".cargo/config.toml");
+    write(
+        &config,
+        "[build]\nrustflags = [\"--cfg=from_build\"]\n\n[target.x86_64-unknown-linux-gnu]\nrustflags = [\"--cfg=from_triple\"]\n",
+    );
+    vars.set("CARGO_BUILD_RUSTFLAGS", "-Dwarnings");
+
+    let cli = ["target.x86_64-unknown-linux-gnu.rustflags=[\"--cfg=from_cli\"]"];
+    let report = vars.flag_sources(&dir, "x86_64-unknown-linux-gnu", &cli);
+
+    let flags: Vec<(String, FlagSource)> = report
+        .flags()
+        .map(|(flag, source)| (flag.to_string(), source.clone()))
+        .collect();
+    let key = "target.x86_64-unknown-linux-gnu.rustflags".to_owned();
+    assert_eq!(
+        flags,
+        [
+            (
+                "--cfg from_triple".to_owned(),
+                FlagSource::ConfigFile {
+                    path: config.clone(),
+                    key: key.clone(),
+                },
+            ),
+            (
+                "--cfg from_cli".to_owned(),
+                FlagSource::CommandLine {
+                    config: cli[0].to_owned(),
+                    key,
+                },
+            ),
+        ],
+    );
+
+    let ignored: Vec<&FlagSource> = report
+        .sources()
+        .iter()
+        .filter(|sourced| !sourced.used)
+        .map(|sourced| &sourced.source)
+        .collect();
+    assert_eq!(ignored.len(), 2);
+    assert_eq!(
+        *ignored[1],
+        FlagSource::Env("CARGO_BUILD_RUSTFLAGS".to_owned()),
+    );
+
+    let expected = format!(
+        "\
+`target.x86_64-unknown-linux-gnu.rustflags` in {config}:
+    --cfg from_triple
+
+--config {cli}:
+    --cfg from_cli
+
+`build.rustflags` in {config} (ignored):
+    --cfg from_build
+
+environment variable CARGO_BUILD_RUSTFLAGS (ignored):
+    --deny warnings
+",
+        config = config.display(),
+        cli = cli[0],
+    );
+    assert_eq!(report.to_string(), expected);
+
+    vars.set("RUSTFLAGS", "");
+    let report = vars.flag_sources(&dir, "x86_64-unknown-linux-gnu", &cli);
+    assert_eq!(report.flags().count(), 0);
+    assert!(report.sources()[0].used);
+    assert!(report.sources()[1..].iter().all(|sourced| !sourced.used));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_config_file() {
+    let (dir, mut vars) = tempdir("cli-file");
+    write(
+        &dir.join(".cargo/config.toml"),
+        "[build]\nrustflags = [\"--cfg=arr\"]\n",
+    );
+    let extra = dir.join("extra.toml");
+    write(&extra, "[build]\nrustflags = [\"--cfg=from_cli_file\"]\n");
+    vars.set("CARGO_BUILD_RUSTFLAGS", "[\"--cfg=from_env\"]");
+
+    // Every --config argument takes precedence over the environment.
+    let report = vars.flag_sources(&dir, "x86_64-unknown-linux-gnu", &[extra.to_str().unwrap()]);
+    let flags: Vec<String> = report.flags().map(|(flag, _)| flag.to_string()).collect();
+    assert_eq!(
+        flags,
+        ["--cfg arr", "--cfg from_env", "--cfg from_cli_file"],
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_toml_syntax() {
+    let (dir, vars) = tempdir("toml");
+    write(
+        &dir.join(".cargo/config.toml"),
+        r#"# Cargo config with the TOML syntax that appears in practice.
+[alias]
+b = "build"
+xtask = ["run", "--package", 'xtask', "--"]
+
+[[patch.unused]]
+name = "first"
+[[patch.unused]]
+name = "second"
+
+[env]
+CC = { value = "clang", force = true, relative = false }
+
+[build]
+jobs = 8
+rustc-wrapper = 'C:\Tools\sccache.exe'
+
+[profile.release]
+debug = 1
+lto = "thin"
+opt-level = 3
+strip = false
+split-debuginfo = "off"
+
+[target."x86_64-unknown-linux-gnu"]
+linker = "clang"
+rustflags = [
+    "-Clink-arg=-fuse-ld=lld",  # comment after a value
+    '-Clink-arg=C:\lib\path',
+    "-Clink-arg=tab\there\u00e9",
+    """-Clink-arg=multi \
+       # not a comment
+    line""",
+    '''
+-Clink-arg=raw\n''',
+]
+"#,
+    );
+
+    assert_eq!(
+        vars.rustflags(&dir, "x86_64-unknown-linux-gnu"),
+        [
+            "-C link-arg=-fuse-ld=lld",
+            "-C link-arg=C:\\lib\\path",
+            "-C link-arg=tab\there\u{e9}",
+            "-C link-arg=multi # not a comment\n    line",
+            "-C link-arg=raw\\n",
+        ],
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,113 @@
+use rustflags::{BuildContext, Flag};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+fn context(vars: &[(&str, &str)]) -> BuildContext {
+    let vars: BTreeMap<&str, &str> = vars.iter().copied().collect();
+    BuildContext::from_env_with(|key: &str| vars.get(key).map(OsString::from))
+}
+
+#[test]
+fn test_build_context() {
+    let context = context(&[
+        ("TARGET", "x86_64-unknown-linux-gnu"),
+        ("HOST", "x86_64-unknown-linux-gnu"),
+        ("PROFILE", "release"),
+        ("OPT_LEVEL", "3"),
+        ("DEBUG", "false"),
+        ("CARGO_CFG_TARGET_FEATURE", "fxsr,sse,sse2"),
+        ("CARGO_CFG_PANIC", "unwind"),
+        ("RUSTC", "rustc"),
+        ("RUSTC_LINKER", "/usr/bin/clang"),
+        ("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-cpu=native"),
+    ]);
+
+    assert_eq!(context.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(context.host.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(context.profile.as_deref(), Some("release"));
+    assert_eq!(context.opt_level.as_deref(), Some("3"));
+    assert_eq!(context.debug, Some(false));
+    assert_eq!(context.target_features, ["fxsr", "sse", "sse2"]);
+    assert_eq!(context.panic.as_deref(), Some("unwind"));
+    assert_eq!(context.rustc.as_deref(), Some(Path::new("rustc")));
+    assert_eq!(context.linker.as_deref(), Some(Path::new("/usr/bin/clang")));
+    assert_eq!(
+        context.flags,
+        [Flag::Codegen {
+            opt: "target-cpu".to_owned(),
+            value: Some("native".to_owned()),
+        }],
+    );
+    assert!(context.conflicts.is_empty());
+}
+
+#[test]
+fn test_conflicts() {
+    let context = context(&[
+        ("OPT_LEVEL", "0"),
+        ("DEBUG", "true"),
+        ("CARGO_CFG_TARGET_FEATURE", "sse,sse2"),
+        ("RUSTC_LINKER", "cc"),
+        (
+            "CARGO_ENCODED_RUSTFLAGS",
+            "-Copt-level=3\x1f-Cdebuginfo=0\x1f-Ctarget-feature=+avx2\x1f-Clinker=clang\x1f-Copt-level=s",
+        ),
+    ]);
+
+    assert_eq!(context.opt_level.as_deref(), Some("s"));
+    assert_eq!(context.debug, Some(false));
+    assert_eq!(context.target_features, ["avx2", "sse", "sse2"]);
+    assert_eq!(context.linker.as_deref(), Some(Path::new("clang")));
+
+    let conflicts: Vec<String> = context.conflicts.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        conflicts,
+        [
+            "OPT_LEVEL=0 conflicts with `-C opt-level=s` in rustflags",
+            "DEBUG=true conflicts with `-C debuginfo=0` in rustflags",
+            "CARGO_CFG_TARGET_FEATURE=sse,sse2 conflicts with `-C target-feature=+avx2` in rustflags",
+            "RUSTC_LINKER=cc conflicts with `-C linker=clang` in rustflags",
+        ],
+    );
+
+    let underscore = self::context(&[
+        ("OPT_LEVEL", "0"),
+        ("CARGO_ENCODED_RUSTFLAGS", "-Copt_level=3"),
+    ]);
+    assert_eq!(underscore.opt_level.as_deref(), Some("3"));
+    let conflicts: Vec<String> = underscore
+        .conflicts
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        conflicts,
+        ["OPT_LEVEL=0 conflicts with `-C opt_level=3` in rustflags"],
+    );
+}
+
+#[test]
+fn test_target_features() {
+    let context = context(&[
+        ("CARGO_CFG_TARGET_FEATURE", "fxsr,sse,sse2"),
+        (
+            "CARGO_ENCODED_RUSTFLAGS",
+            "-Ctarget-feature=+aes,-sse2\x1f-Ctarget-feature=-aes,+sse2,+crt-static",
+        ),
+    ]);
+    assert_eq!(
+        context.target_features,
+        ["crt-static", "fxsr", "sse", "sse2"]
+    );
+
+    let underscore = self::context(&[
+        ("CARGO_CFG_TARGET_FEATURE", "sse"),
+        ("CARGO_ENCODED_RUSTFLAGS", "-Ctarget_feature=+avx2"),
+    ]);
+    assert_eq!(underscore.target_features, ["avx2", "sse"]);
+
+    let without_env = self::context(&[("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-feature=+avx2")]);
+    assert_eq!(without_env.target_features, ["avx2"]);
+    assert!(without_env.conflicts.is_empty());
+}
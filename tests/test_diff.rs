@@ -0,0 +1,131 @@
+use rustflags::{Change, Flag};
+use std::ffi::OsStr;
+
+fn flags(rustflags: &str) -> Vec<Flag> {
+    let args: Vec<&str> = rustflags.split_whitespace().collect();
+    rustflags::from_encoded(OsStr::new(&args.join("\x1F"))).collect()
+}
+
+fn diff(old: &str, new: &str) -> Vec<String> {
+    rustflags::diff(&flags(old), &flags(new))
+        .changes()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_equivalent() {
+    for (old, new) in [
+        ("-O", "-Copt-level=2"),
+        ("-Copt-level=3 -O", "-C opt_level=2"),
+        ("-g", "-Cdebuginfo=full"),
+        ("-Cprefer-dynamic", "-Cprefer-dynamic=yes"),
+        ("-Zfoo=1 -Zfoo=2", "-Zfoo=2"),
+        ("-L /a -L /b -L /a -la -lb", "-L /a -L /b -la -lb"),
+        ("--cfg a --cfg b", "--cfg b --cfg a --cfg b"),
+        (
+            "-Ctarget-feature=+avx2,+fma",
+            "-Ctarget-feature=+fma -Ctarget-feature=+avx2",
+        ),
+        ("-Dwarnings -Aunused", "-Aunused -Dwarnings -Dwarnings"),
+        (
+            "-Aclippy::pedantic -Wclippy::unwrap_used",
+            "-Wclippy::unwrap-used -Aclippy::pedantic -Wclippy::unwrap_used",
+        ),
+    ] {
+        assert!(diff(old, new).is_empty());
+    }
+}
+
+#[test]
+fn test_changes() {
+    assert_eq!(
+        diff(
+            "-O --cfg a --target=x86_64-unknown-linux-gnu -Clinker=cc",
+            "-Copt-level=3 --cfg b --target aarch64-unknown-linux-gnu",
+        ),
+        [
+            "- --cfg a",
+            "+ --cfg b",
+            "~ --target: x86_64-unknown-linux-gnu -> aarch64-unknown-linux-gnu",
+            "- -C linker: cc",
+            "~ -C opt-level: 2 -> 3",
+        ],
+    );
+
+    assert_eq!(
+        diff(
+            "-Ctarget-feature=+avx2,-fma",
+            "-Ctarget-feature=+avx2 -Ctarget-feature=+fma"
+        ),
+        ["~ -C target-feature: +avx2,-fma -> +avx2,+fma"],
+    );
+    assert_eq!(
+        diff(
+            "-Cllvm-args=-a -Cllvm-args=-b",
+            "-Cllvm-args=-b -Cllvm-args=-a"
+        ),
+        ["~ -C llvm-args: -a -b -> -b -a"],
+    );
+
+    assert_eq!(
+        diff("-l static=a -l static=b", "-l static=b -l static=a"),
+        ["~ -l: static=a static=b -> static=b static=a"],
+    );
+    assert_eq!(
+        diff("-L /a -L /b -lfoo", "-L /b -L /a -L /b -lfoo"),
+        ["~ -L: /a /b -> /b /a"],
+    );
+    assert_eq!(
+        diff(
+            "-Clink-arg=-la -Clink-arg=-lb",
+            "-Clink-arg=-lb -Clink-arg=-la"
+        ),
+        ["~ -C link-arg: -la -lb -> -lb -la"],
+    );
+
+    let changes = rustflags::diff(&flags("-Zshare-generics"), &[]);
+    assert_eq!(
+        changes.changes(),
+        [Change::Removed {
+            setting: "-Z share-generics".to_owned(),
+            value: Some("yes".to_owned()),
+        }],
+    );
+}
+
+#[test]
+fn test_lint_order() {
+    // The group comes last and overrides the individual lint.
+    assert_eq!(
+        diff(
+            "-Aclippy::pedantic -Wclippy::unwrap_used",
+            "-Wclippy::unwrap_used -Aclippy::pedantic",
+        ),
+        ["~ lint clippy::unwrap_used: --warn -> --warn, then --allow clippy::pedantic"],
+    );
+
+    // Individual lints are independent of each other.
+    assert!(diff(
+        "-Adead_code -Wunused_imports",
+        "-Wunused_imports -Adead_code"
+    )
+    .is_empty());
+
+    assert_eq!(
+        diff("-Dwarnings", "-Wwarnings"),
+        ["~ lint warnings: --deny -> --warn"],
+    );
+
+    // Later flags do not override forbid or force-warn.
+    assert_eq!(
+        diff("-Funsafe_code -Aunsafe_code", "-Aunsafe_code"),
+        ["~ lint unsafe_code: --forbid -> --allow"],
+    );
+    assert!(diff(
+        "--force-warn deprecated -Adeprecated",
+        "--force-warn deprecated"
+    )
+    .is_empty());
+}
@@ -0,0 +1,71 @@
+use rustflags::Flag;
+use std::ffi::{OsStr, OsString};
+use std::process::Command;
+
+fn envs(cmd: &Command) -> Vec<(&OsStr, Option<&OsStr>)> {
+    let mut envs: Vec<_> = cmd.get_envs().collect();
+    envs.sort();
+    envs
+}
+
+#[test]
+fn test_forward() {
+    let env = |key: &str| -> Option<OsString> {
+        (key == "CARGO_ENCODED_RUSTFLAGS")
+            .then(|| "--target\x1fx86_64-unknown-linux-gnu\x1f-Copt-level=3\x1f--cfg=a".into())
+    };
+    let mut cmd = Command::new("cargo");
+    rustflags::forward_rustflags_with(&mut cmd, env, |flags| {
+        flags.retain(|flag| !matches!(flag, Flag::Target(_)));
+        flags.push(Flag::Cfg {
+            name: "nested".to_owned(),
+            value: None,
+        });
+    });
+    assert_eq!(
+        envs(&cmd),
+        [
+            (
+                OsStr::new("CARGO_ENCODED_RUSTFLAGS"),
+                Some(OsStr::new("-Copt-level=3\x1f--cfg=a\x1f--cfg\x1fnested")),
+            ),
+            (OsStr::new("RUSTFLAGS"), None),
+        ],
+    );
+}
+
+#[test]
+fn test_forward_unrecognized() {
+    let env = |key: &str| -> Option<OsString> {
+        (key == "CARGO_ENCODED_RUSTFLAGS")
+            .then(|| "--diagnostic-width=80\x1f-C\x1fopt-level=3\x1f-Zfoo\x1f--cfg\x1fa".into())
+    };
+    let mut cmd = Command::new("cargo");
+    rustflags::forward_rustflags_with(&mut cmd, env, |flags| {
+        for flag in flags {
+            if let Flag::Cfg { name, .. } = flag {
+                *name = "b".to_owned();
+            }
+        }
+    });
+    assert_eq!(
+        envs(&cmd)[0].1,
+        Some(OsStr::new(
+            "--diagnostic-width=80\x1f-C\x1fopt-level=3\x1f-Zfoo\x1f--cfg\x1fb"
+        )),
+    );
+}
+
+#[test]
+fn test_set_empty() {
+    let mut cmd = Command::new("cargo");
+    cmd.env("RUSTFLAGS", "-Dwarnings");
+    rustflags::set_cargo_rustflags(&mut cmd, []);
+    assert_eq!(
+        envs(&cmd),
+        [
+            (OsStr::new("CARGO_ENCODED_RUSTFLAGS"), Some(OsStr::new(""))),
+            (OsStr::new("RUSTFLAGS"), None),
+        ],
+    );
+}
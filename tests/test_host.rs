@@ -0,0 +1,70 @@
+use rustflags::{Flag, HostInfo};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+fn host_info(vars: &[(&str, &str)]) -> HostInfo {
+    let vars: BTreeMap<&str, &str> = vars.iter().copied().collect();
+    HostInfo::from_env_with(|key: &str| vars.get(key).map(OsString::from))
+}
+
+#[test]
+fn test_native_build() {
+    let mut vars = vec![
+        ("HOST", "x86_64-unknown-linux-gnu"),
+        ("TARGET", "x86_64-unknown-linux-gnu"),
+        ("OUT_DIR", "/work/target/debug/build/demo-1a2b3c/out"),
+    ];
+
+    let info = host_info(&vars);
+    assert!(!info.cross);
+    assert!(!info.explicit_target);
+    assert!(info.applies_to_host);
+
+    let flags = [Flag::Z("sanitizer=address".to_owned())];
+    assert_eq!(info.host_flags(&flags), [&flags[0]]);
+
+    vars.push(("CARGO_TARGET_APPLIES_TO_HOST", "false"));
+    let info = host_info(&vars);
+    assert!(!info.applies_to_host);
+    assert!(info.host_flags(&flags).is_empty());
+}
+
+#[test]
+fn test_explicit_target() {
+    // `cargo build --target x86_64-unknown-linux-gnu` on the same host.
+    let info = host_info(&[
+        ("HOST", "x86_64-unknown-linux-gnu"),
+        ("TARGET", "x86_64-unknown-linux-gnu"),
+        (
+            "OUT_DIR",
+            "/work/target/x86_64-unknown-linux-gnu/debug/build/demo-1a2b3c/out",
+        ),
+    ]);
+    assert!(!info.cross);
+    assert!(info.explicit_target);
+    assert!(!info.applies_to_host);
+
+    let info = host_info(&[
+        ("HOST", "x86_64-unknown-linux-gnu"),
+        ("TARGET", "aarch64-unknown-linux-gnu"),
+        (
+            "OUT_DIR",
+            "/work/target/aarch64-unknown-linux-gnu/release/build/demo-1a2b3c/out",
+        ),
+    ]);
+    assert!(info.cross);
+    assert!(info.explicit_target);
+    let flags = [Flag::Z("sanitizer=address".to_owned())];
+    assert!(info.host_flags(&flags).is_empty());
+}
+
+#[test]
+fn test_target_from_cfg() {
+    let info = host_info(&[
+        ("HOST", "x86_64-unknown-linux-gnu"),
+        ("CARGO_CFG_TARGET_ARCH", "wasm32"),
+        ("CARGO_CFG_TARGET_OS", "unknown"),
+    ]);
+    assert!(info.cross);
+    assert!(!info.applies_to_host);
+}
@@ -0,0 +1,113 @@
+use rustflags::{Flag, LinkModifierPrefix, LinkerConfig, LinkerFlavor, SelfContained};
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+fn linker_config(encoded: &str, target: &str) -> LinkerConfig {
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(encoded)).collect();
+    LinkerConfig::new(&flags, target)
+}
+
+#[test]
+fn test_inferred_from_target() {
+    let config = linker_config("", "x86_64-unknown-linux-gnu");
+    assert_eq!(config.linker, None);
+    assert_eq!(config.flavor, LinkerFlavor::Gcc);
+    assert!(!config.explicit_flavor);
+    assert_eq!(config.self_contained, SelfContained::Inferred);
+    assert!(!config.uses_lld());
+
+    let config = linker_config("", "x86_64-pc-windows-msvc");
+    assert_eq!(config.flavor, LinkerFlavor::Msvc);
+
+    let config = linker_config("", "wasm32-unknown-unknown");
+    assert_eq!(config.flavor, LinkerFlavor::WasmLd);
+    assert!(config.uses_lld());
+
+    let config = linker_config("", "wasm32-unknown-emscripten");
+    assert_eq!(config.flavor, LinkerFlavor::Em);
+}
+
+#[test]
+fn test_inferred_from_linker() {
+    let config = linker_config(
+        "-Clinker=/usr/bin/aarch64-linux-gnu-gcc",
+        "aarch64-unknown-linux-gnu",
+    );
+    assert_eq!(
+        config.linker,
+        Some(PathBuf::from("/usr/bin/aarch64-linux-gnu-gcc")),
+    );
+    assert_eq!(config.flavor, LinkerFlavor::Gcc);
+    assert!(!config.explicit_flavor);
+
+    let config = linker_config("-C\x1flinker=lld-link.exe", "x86_64-pc-windows-msvc");
+    assert_eq!(config.flavor, LinkerFlavor::LldLink);
+    assert!(config.uses_lld());
+
+    let config = linker_config("-Clinker=/usr/bin/ld.lld", "x86_64-unknown-linux-gnu");
+    assert_eq!(config.flavor, LinkerFlavor::LdLld);
+    assert!(config.uses_lld());
+
+    let config = linker_config("-Clinker=ld64.lld", "aarch64-apple-darwin");
+    assert_eq!(config.flavor, LinkerFlavor::Ld64Lld);
+    assert!(config.uses_lld());
+}
+
+#[test]
+fn test_explicit() {
+    let config = linker_config(
+        "-Clinker=clang\x1f-Clinker-flavor=ld.lld\x1f-Clink-self-contained=+linker,-crto\x1f-Zlinker-features=+lld",
+        "x86_64-unknown-linux-gnu",
+    );
+    assert_eq!(config.flavor, LinkerFlavor::LdLld);
+    assert!(config.explicit_flavor);
+    assert_eq!(
+        config.self_contained,
+        SelfContained::Components(vec![
+            (LinkModifierPrefix::Enable, "linker".to_owned()),
+            (LinkModifierPrefix::Disable, "crto".to_owned()),
+        ]),
+    );
+    assert_eq!(
+        config.linker_features,
+        [(LinkModifierPrefix::Enable, "lld".to_owned())],
+    );
+
+    let config = linker_config("-Clink-self-contained=no", "x86_64-unknown-linux-musl");
+    assert_eq!(config.self_contained, SelfContained::No);
+}
+
+#[test]
+fn test_link_args() {
+    let config = linker_config(
+        "-Clink-arg=-Wl,--gc-sections\x1f-Cpre-link-arg=-nostartfiles\x1f-C\x1flink-args=-la  -lb\x1f-Clink-arg=-fuse-ld=lld",
+        "x86_64-unknown-linux-gnu",
+    );
+    assert_eq!(
+        config.link_args,
+        [
+            "-Wl,--gc-sections",
+            "-nostartfiles",
+            "-la",
+            "-lb",
+            "-fuse-ld=lld"
+        ],
+    );
+    assert_eq!(config.flavor, LinkerFlavor::Gcc);
+    assert!(config.uses_lld());
+}
+
+#[test]
+fn test_underscore_spelling() {
+    let config = linker_config(
+        "-Clink_arg=-fuse-ld=lld\x1f-Clinker_flavor=msvc\x1f-Zlinker_features=+lld",
+        "x86_64-unknown-linux-gnu",
+    );
+    assert_eq!(config.flavor, LinkerFlavor::Msvc);
+    assert!(config.explicit_flavor);
+    assert_eq!(config.link_args, ["-fuse-ld=lld"]);
+    assert_eq!(
+        config.linker_features,
+        [(LinkModifierPrefix::Enable, "lld".to_owned())],
+    );
+}
@@ -0,0 +1,124 @@
+use rustflags::Flag;
+
+#[test]
+fn test_from_cargo_lints() {
+    let manifest = r#"
+[package]
+name = "demo"
+version = "0.0.0"
+
+[lints.rust]
+unsafe_code = "forbid"
+unused = { level = "warn", priority = -1 }
+unexpected_cfgs = { level = "warn", check-cfg = ['cfg(fuzzing)', 'cfg(tokio_unstable)'] }
+
+[lints.clippy]
+pedantic = { level = "deny", priority = -1 }
+module_name_repetitions = "allow"
+
+[lints.cargo]
+foo = "warn"
+"#;
+
+    assert_eq!(
+        rustflags::from_cargo_lints(manifest).unwrap(),
+        [
+            Flag::Warn("unused".to_owned()),
+            Flag::Deny("clippy::pedantic".to_owned()),
+            Flag::Forbid("unsafe_code".to_owned()),
+            Flag::Warn("unexpected_cfgs".to_owned()),
+            Flag::Allow("clippy::module_name_repetitions".to_owned()),
+            Flag::CheckCfg("cfg(fuzzing)".to_owned()),
+            Flag::CheckCfg("cfg(tokio_unstable)".to_owned()),
+        ],
+    );
+
+    let workspace = "[workspace.lints.rust]\nmissing_docs = \"deny\"\n";
+    assert_eq!(
+        rustflags::from_cargo_lints(workspace).unwrap(),
+        [Flag::Deny("missing_docs".to_owned())],
+    );
+
+    // Sorted by lint name without the tool.
+    let manifest =
+        "[lints.rust]\nambiguous_glob_reexports = \"deny\"\n[lints.clippy]\nall = \"warn\"\n";
+    assert_eq!(
+        rustflags::from_cargo_lints(manifest).unwrap(),
+        [
+            Flag::Deny("ambiguous_glob_reexports".to_owned()),
+            Flag::Warn("clippy::all".to_owned()),
+        ],
+    );
+
+    assert_eq!(rustflags::from_cargo_lints("[package]\n").unwrap(), []);
+}
+
+#[test]
+fn test_invalid_lints() {
+    let error = rustflags::from_cargo_lints("[lints]\nworkspace = true\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid manifest: lints are inherited from the workspace manifest",
+    );
+
+    let error = rustflags::from_cargo_lints("[lints.rust]\nunused = \"loud\"\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid manifest: unknown lint level `loud` for `lints.rust.unused`",
+    );
+}
+
+#[test]
+fn test_to_cargo_lints() {
+    let flags = [
+        Flag::Deny("warnings".to_owned()),
+        Flag::Warn("clippy::pedantic".to_owned()),
+        Flag::Allow("clippy::module-name-repetitions".to_owned()),
+        Flag::Forbid("unsafe_code".to_owned()),
+        Flag::Allow("dead_code".to_owned()),
+        Flag::Warn("dead_code".to_owned()),
+        Flag::CheckCfg("cfg(fuzzing)".to_owned()),
+        Flag::ForceWarn("deprecated".to_owned()),
+        Flag::Codegen {
+            opt: "opt-level".to_owned(),
+            value: Some("3".to_owned()),
+        },
+    ];
+
+    let expected = r#"[lints.rust]
+warnings = { level = "deny", priority = -1 }
+unsafe_code = "forbid"
+dead_code = "warn"
+unexpected_cfgs = { level = "warn", check-cfg = ['cfg(fuzzing)'] }
+
+[lints.clippy]
+pedantic = { level = "warn", priority = -1 }
+module_name_repetitions = "allow"
+"#;
+    assert_eq!(rustflags::to_cargo_lints(&flags), expected);
+
+    // Converting back yields flags with the same effect.
+    let manifest = rustflags::to_cargo_lints(&flags);
+    assert_eq!(
+        rustflags::from_cargo_lints(&manifest).unwrap(),
+        [
+            Flag::Deny("warnings".to_owned()),
+            Flag::Warn("clippy::pedantic".to_owned()),
+            Flag::Forbid("unsafe_code".to_owned()),
+            Flag::Warn("unexpected_cfgs".to_owned()),
+            Flag::Allow("clippy::module_name_repetitions".to_owned()),
+            Flag::Warn("dead_code".to_owned()),
+            Flag::CheckCfg("cfg(fuzzing)".to_owned()),
+        ],
+    );
+
+    // A later level does not override forbid.
+    let forbidden = [
+        Flag::Forbid("unsafe_code".to_owned()),
+        Flag::Allow("unsafe-code".to_owned()),
+    ];
+    assert_eq!(
+        rustflags::to_cargo_lints(&forbidden),
+        "[lints.rust]\nunsafe_code = \"forbid\"\n",
+    );
+}
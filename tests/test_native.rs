@@ -0,0 +1,241 @@
+mod common;
+
+use rustflags::{Flag, LinkKind, LinkerFlavor, NativeLibrary};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn touch(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, b"").unwrap();
+}
+
+fn find(tokens: &[OsString], target: &str) -> Vec<NativeLibrary> {
+    let encoded = tokens.join(&OsString::from("\x1f"));
+    let flags: Vec<Flag> = rustflags::from_encoded(&encoded).collect();
+    rustflags::find_native_libraries(&flags, target)
+}
+
+fn arg(prefix: &str, path: &Path) -> OsString {
+    let mut arg = OsString::from(prefix);
+    arg.push(path);
+    arg
+}
+
+#[test]
+fn test_linux() {
+    let dir = common::tempdir("native-linux");
+    touch(&dir.join("a/libfoo.a"));
+    touch(&dir.join("b/libfoo.so"));
+    touch(&dir.join("b/libbar.a"));
+    touch(&dir.join("deps/libbaz.a"));
+
+    let libraries = find(
+        &[
+            arg("-Lnative=", &dir.join("a")),
+            arg("-L", &dir.join("b")),
+            arg("-Ldependency=", &dir.join("deps")),
+            OsString::from("-lfoo"),
+            OsString::from("-lstatic=foo"),
+            OsString::from("-lbar"),
+            OsString::from("-lbaz"),
+            OsString::from("-ldylib:+verbatim=libbar.a"),
+        ],
+        "x86_64-unknown-linux-gnu",
+    );
+
+    let paths: Vec<Option<PathBuf>> = libraries.iter().map(|lib| lib.path.clone()).collect();
+    assert_eq!(
+        paths,
+        [
+            Some(dir.join("a/libfoo.a")),
+            Some(dir.join("a/libfoo.a")),
+            Some(dir.join("b/libbar.a")),
+            None,
+            Some(dir.join("b/libbar.a")),
+        ],
+    );
+    assert_eq!(libraries[1].kind, LinkKind::Static);
+    assert!(libraries[4].verbatim);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_dylib_preference() {
+    let dir = common::tempdir("native-preference");
+    touch(&dir.join("libfoo.a"));
+    touch(&dir.join("libfoo.so"));
+
+    let libraries = find(
+        &[
+            arg("-L", &dir),
+            OsString::from("-lfoo"),
+            OsString::from("-lstatic=foo"),
+        ],
+        "x86_64-unknown-linux-gnu",
+    );
+    assert_eq!(libraries[0].path, Some(dir.join("libfoo.so")));
+    assert_eq!(libraries[1].path, Some(dir.join("libfoo.a")));
+
+    let libraries = find(
+        &[arg("-L", &dir), OsString::from("-lstatic=bar:foo")],
+        "x86_64-unknown-linux-gnu",
+    );
+    assert_eq!(libraries[0].name, "bar");
+    assert_eq!(libraries[0].path, Some(dir.join("libfoo.a")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_msvc() {
+    let dir = common::tempdir("native-msvc");
+    touch(&dir.join("foo.lib"));
+    touch(&dir.join("libfoo.so"));
+
+    let libraries = find(
+        &[arg("-L", &dir), OsString::from("-lfoo")],
+        "x86_64-pc-windows-msvc",
+    );
+    assert_eq!(libraries[0].path, Some(dir.join("foo.lib")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_framework() {
+    let dir = common::tempdir("native-framework");
+    fs::create_dir_all(dir.join("frameworks/Foo.framework")).unwrap();
+
+    let libraries = find(
+        &[
+            arg("-Lnative=", &dir),
+            arg("-Lframework=", &dir.join("frameworks")),
+            OsString::from("-lframework=Foo"),
+        ],
+        "aarch64-apple-darwin",
+    );
+    assert_eq!(
+        libraries[0].path,
+        Some(dir.join("frameworks/Foo.framework")),
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+fn linker_args(encoded: &str, flavor: LinkerFlavor, staticlib: bool) -> Vec<OsString> {
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(encoded)).collect();
+    let target = match flavor {
+        LinkerFlavor::Msvc => "x86_64-pc-windows-msvc",
+        _ => "x86_64-unknown-linux-gnu",
+    };
+    if staticlib {
+        rustflags::c_linker_args_for_staticlib(&flags, flavor, target)
+    } else {
+        rustflags::c_linker_args(&flags, flavor, target)
+    }
+}
+
+#[test]
+fn test_c_linker_args_gnu() {
+    let encoded = "-Lnative=/opt/lib\x1f-Ldependency=/target/deps\x1f-lstatic:+whole-archive=foo\x1f-lstatic:-bundle=bar\x1f-lz\x1f-ldylib:-as-needed,+verbatim=libm.so.6";
+    assert_eq!(
+        linker_args(encoded, LinkerFlavor::Gcc, false),
+        [
+            "-L/opt/lib",
+            "-Wl,-Bstatic",
+            "-Wl,--whole-archive",
+            "-lfoo",
+            "-Wl,--no-whole-archive",
+            "-lbar",
+            "-Wl,--as-needed",
+            "-Wl,-Bdynamic",
+            "-lz",
+            "-Wl,--no-as-needed",
+            "-l:libm.so.6",
+            "-Wl,--as-needed",
+        ],
+    );
+    assert_eq!(
+        linker_args(encoded, LinkerFlavor::Gcc, true),
+        [
+            "-L/opt/lib",
+            "-Wl,-Bstatic",
+            "-lbar",
+            "-Wl,--as-needed",
+            "-Wl,-Bdynamic",
+            "-lz",
+            "-Wl,--no-as-needed",
+            "-l:libm.so.6",
+            "-Wl,--as-needed",
+        ],
+    );
+    assert_eq!(
+        linker_args("-lstatic=foo", LinkerFlavor::Ld, false),
+        ["-Bstatic", "-lfoo", "-Bdynamic"],
+    );
+    assert_eq!(
+        linker_args("-lfoo:bar\x1f-lstatic=baz:qux", LinkerFlavor::Gcc, false),
+        [
+            "-Wl,--as-needed",
+            "-lbar",
+            "-Wl,-Bstatic",
+            "-lqux",
+            "-Wl,-Bdynamic"
+        ],
+    );
+}
+
+#[test]
+fn test_c_linker_args_apple() {
+    let dir = common::tempdir("native-apple");
+    touch(&dir.join("libfoo.a"));
+
+    let encoded = arg("-Lnative=", &dir);
+    let encoded = [
+        encoded.to_str().unwrap(),
+        "-lstatic:+whole-archive=foo",
+        "-lstatic=bar",
+        "-ldylib:-as-needed=z",
+    ]
+    .join("\x1f");
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(&encoded)).collect();
+    assert_eq!(
+        rustflags::c_linker_args(&flags, LinkerFlavor::Gcc, "aarch64-apple-darwin"),
+        [
+            arg("-L", &dir),
+            arg("-Wl,-force_load,", &dir.join("libfoo.a")),
+            OsString::from("-lbar"),
+            OsString::from("-lz"),
+        ],
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_c_linker_args_msvc() {
+    let encoded = "-L/opt/lib\x1f-Lframework=/Library/Frameworks\x1f-lstatic:+whole-archive=foo\x1f-lkernel32\x1f-ldylib:+verbatim=bar.dll.lib";
+    assert_eq!(
+        linker_args(encoded, LinkerFlavor::Msvc, false),
+        [
+            "/LIBPATH:/opt/lib",
+            "/WHOLEARCHIVE:foo.lib",
+            "kernel32.lib",
+            "bar.dll.lib",
+        ],
+    );
+}
+
+#[test]
+fn test_c_linker_args_framework() {
+    assert_eq!(
+        linker_args(
+            "-Lframework=/Library/Frameworks\x1f-lframework=CoreFoundation",
+            LinkerFlavor::Gcc,
+            false,
+        ),
+        ["-F/Library/Frameworks", "-framework", "CoreFoundation"],
+    );
+}
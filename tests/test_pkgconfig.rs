@@ -0,0 +1,102 @@
+use rustflags::{Flag, LibraryKind, LinkKind, LinkModifier, LinkModifierPrefix};
+use std::path::PathBuf;
+
+fn link(kind: LinkKind, name: &str) -> Flag {
+    Flag::Link {
+        kind,
+        modifiers: Vec::new(),
+        name: name.to_owned(),
+        rename: None,
+    }
+}
+
+fn link_arg(arg: &str) -> Flag {
+    Flag::Codegen {
+        opt: "link-arg".to_owned(),
+        value: Some(arg.to_owned()),
+    }
+}
+
+#[test]
+fn test_from_pkg_config() {
+    // pkg-config --libs --static openssl
+    let output = "-L/usr/local/Cellar/openssl@3/3.3.1/lib -lssl -lcrypto -lz -pthread -Wl,-rpath,/opt/my\\ lib\n";
+    assert_eq!(
+        rustflags::from_pkg_config(output),
+        [
+            Flag::LibrarySearchPath {
+                kind: LibraryKind::Native,
+                path: PathBuf::from("/usr/local/Cellar/openssl@3/3.3.1/lib"),
+            },
+            link(LinkKind::Dylib, "ssl"),
+            link(LinkKind::Dylib, "crypto"),
+            link(LinkKind::Dylib, "z"),
+            link_arg("-pthread"),
+            link_arg("-Wl,-rpath,/opt/my lib"),
+        ],
+    );
+
+    // pkg-config --cflags --libs with frameworks
+    let output = "-I/opt/include -DFOO=1 -O2 -fPIC -isystem /opt/sys -F/Library/Frameworks -framework CoreFoundation -l:libbar.a /opt/lib/libbaz.a -Xlinker -z";
+    assert_eq!(
+        rustflags::from_pkg_config(output),
+        [
+            Flag::LibrarySearchPath {
+                kind: LibraryKind::Framework,
+                path: PathBuf::from("/Library/Frameworks"),
+            },
+            link(LinkKind::Framework, "CoreFoundation"),
+            Flag::Link {
+                kind: LinkKind::Dylib,
+                modifiers: vec![(LinkModifierPrefix::Enable, LinkModifier::Verbatim)],
+                name: "libbar.a".to_owned(),
+                rename: None,
+            },
+            link_arg("/opt/lib/libbaz.a"),
+            link_arg("-Xlinker"),
+            link_arg("-z"),
+        ],
+    );
+
+    // Separated arguments of compiler options are not link arguments.
+    let output = "-I /usr/include/foo -D FOO -U BAR -include config.h -lfoo";
+    assert_eq!(
+        rustflags::from_pkg_config(output),
+        [link(LinkKind::Dylib, "foo")],
+    );
+}
+
+#[test]
+fn test_to_pkg_config_libs() {
+    let flags = [
+        Flag::LibrarySearchPath {
+            kind: LibraryKind::Native,
+            path: PathBuf::from("/opt/my lib"),
+        },
+        Flag::LibrarySearchPath {
+            kind: LibraryKind::Dependency,
+            path: PathBuf::from("/target/deps"),
+        },
+        link(LinkKind::Static, "ssl"),
+        Flag::Link {
+            kind: LinkKind::Dylib,
+            modifiers: Vec::new(),
+            name: "crypto".to_owned(),
+            rename: Some("crypto3".to_owned()),
+        },
+        link(LinkKind::Framework, "Security"),
+        link_arg("-pthread"),
+        Flag::Cfg {
+            name: "ossl300".to_owned(),
+            value: None,
+        },
+    ];
+    let libs = rustflags::to_pkg_config_libs(&flags);
+    assert_eq!(
+        libs,
+        "Libs: -L/opt/my\\ lib -lssl -lcrypto3 -framework Security -pthread",
+    );
+
+    let round_trip = rustflags::from_pkg_config(libs.strip_prefix("Libs:").unwrap());
+    assert_eq!(round_trip.len(), 5);
+}
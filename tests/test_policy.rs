@@ -0,0 +1,91 @@
+use rustflags::{Flag, Policy, Severity};
+use std::ffi::OsStr;
+
+fn flags(rustflags: &str) -> Vec<Flag> {
+    let args: Vec<&str> = rustflags.split_whitespace().collect();
+    rustflags::from_encoded(OsStr::new(&args.join("\x1F"))).collect()
+}
+
+fn denied(policy: &Policy, rustflags: &str) -> Vec<String> {
+    let flags = flags(rustflags);
+    policy
+        .check(&flags)
+        .into_iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.severity, Severity::Error);
+            flags[diagnostic.indices[0]].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_spelling() {
+    let policy = Policy::from_toml(r#"deny = ["-Ctarget-cpu=native"]"#).unwrap();
+    for rustflags in [
+        "-Ctarget-cpu=native",
+        "-C target-cpu=native",
+        "--codegen=target-cpu=native",
+        "--codegen target-cpu=native",
+    ] {
+        assert_eq!(denied(&policy, rustflags), ["-C target-cpu=native"]);
+    }
+    assert!(denied(&policy, "-Ctarget-cpu=x86-64-v3").is_empty());
+}
+
+#[test]
+fn test_any_value() {
+    let policy = Policy::from_toml(r#"deny = ["-Z build-std"]"#).unwrap();
+    assert_eq!(
+        denied(
+            &policy,
+            "-Zbuild-std -Zbuild-std=core,alloc -Zbuild-std-features=panic_immediate_abort"
+        ),
+        ["-Z build-std", "-Z build-std=core,alloc"],
+    );
+
+    let diagnostics = policy.check(&flags("-Zbuild-std=core"));
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "error: `-Z build-std=core` is denied by policy (`-Z build-std`) (`-Z build-std=core`)",
+    );
+}
+
+#[test]
+fn test_wildcard_and_allow() {
+    let policy = Policy::from_toml(
+        r#"
+        deny = ["-Z*", "-Clinker=*"]
+        allow = ["-Zsanitizer=address"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        denied(
+            &policy,
+            "-Zsanitizer=address -Zsanitizer=thread -Zshare-generics -Clinker=clang -Copt-level=3",
+        ),
+        [
+            "-Z sanitizer=thread",
+            "-Z share-generics",
+            "-C linker=clang"
+        ],
+    );
+}
+
+#[test]
+fn test_invalid() {
+    let error = Policy::from_toml(r#"forbid = ["-Zbuild-std"]"#).unwrap_err();
+    assert_eq!(error.to_string(), "invalid policy: unexpected key `forbid`");
+
+    let error = Policy::from_toml(r#"deny = "-Zbuild-std""#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid policy: expected `deny` to be an array of strings",
+    );
+
+    let error = Policy::from_toml(r#"deny = ["-Copt-level=3 -Cdebuginfo=2"]"#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid policy: `-Copt-level=3 -Cdebuginfo=2` is not a single rustc flag",
+    );
+}
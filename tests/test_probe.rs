@@ -0,0 +1,117 @@
+#![cfg(unix)]
+
+mod common;
+
+use rustflags::{Flag, ProbeCommand};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+fn script(path: &Path, contents: &str) {
+    fs::write(path, contents).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// Records its arguments and input, and succeeds if the input mentions "ok".
+fn fake_rustc(dir: &Path) -> PathBuf {
+    let rustc = dir.join("rustc");
+    script(
+        &rustc,
+        &format!(
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > {dir}/args\ncat > {dir}/input\ngrep -q ok {dir}/input\n",
+            dir = dir.display(),
+        ),
+    );
+    rustc
+}
+
+fn args(dir: &Path) -> Vec<String> {
+    let args = fs::read_to_string(dir.join("args")).unwrap();
+    args.lines().map(str::to_owned).collect()
+}
+
+#[test]
+fn test_probe() {
+    let dir = common::tempdir("probe-probe");
+    let rustc = fake_rustc(&dir);
+    let flags: Vec<Flag> = rustflags::from_encoded(OsStr::new(
+        "--cfg\x1ftokio_unstable\x1f--emit=asm\x1f-o\x1fout.s\x1f--out-dir=x\x1f--crate-type=bin\x1f--error-format=json\x1f--test\x1f--print=cfg\x1f-Ctarget-feature=+avx2\x1f--sysroot=/sysroot\x1f-Zbuild-std",
+    ))
+    .collect();
+
+    let mut probe = ProbeCommand::new(&rustc, &flags);
+    probe.target("x86_64-unknown-linux-gnu").out_dir(&dir);
+    assert!(probe.probe("pub fn ok() {}"));
+    assert_eq!(
+        fs::read_to_string(dir.join("input")).unwrap(),
+        "pub fn ok() {}"
+    );
+    assert_eq!(
+        args(&dir),
+        [
+            "--crate-name=rustflags_probe".to_owned(),
+            "--crate-type=lib".to_owned(),
+            "--emit=metadata".to_owned(),
+            format!("--out-dir={}", dir.display()),
+            "--target=x86_64-unknown-linux-gnu".to_owned(),
+            "--cfg".to_owned(),
+            "tokio_unstable".to_owned(),
+            "-C".to_owned(),
+            "target-feature=+avx2".to_owned(),
+            "--sysroot".to_owned(),
+            "/sysroot".to_owned(),
+            "-Z".to_owned(),
+            "build-std".to_owned(),
+            "-".to_owned(),
+        ],
+    );
+
+    assert!(!probe.probe("pub fn fails() {}"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_from_env() {
+    let dir = common::tempdir("probe-env");
+    let rustc = fake_rustc(&dir);
+    let wrapper = dir.join("wrapper");
+    script(
+        &wrapper,
+        &format!(
+            "#!/bin/sh\necho wrapped > {}/wrapped\nexec \"$@\"\n",
+            dir.display()
+        ),
+    );
+
+    let env = |key: &str| -> Option<OsString> {
+        match key {
+            "RUSTC" => Some(rustc.clone().into()),
+            "RUSTC_WRAPPER" => Some(wrapper.clone().into()),
+            "TARGET" => Some("aarch64-unknown-linux-gnu".into()),
+            "OUT_DIR" => Some(dir.clone().into()),
+            "CARGO_ENCODED_RUSTFLAGS" => Some("--target\x1fthumbv7em-none-eabihf\x1f-O".into()),
+            _ => None,
+        }
+    };
+    let probe = ProbeCommand::from_env_with(env);
+    assert!(probe.probe("ok"));
+    assert!(dir.join("wrapped").exists());
+
+    // The target from the flags takes precedence over TARGET.
+    let args = args(&dir);
+    assert!(!args.iter().any(|arg| arg.starts_with("--target=")));
+    assert_eq!(
+        args[4..],
+        [
+            "--target",
+            "thumbv7em-none-eabihf",
+            "-C",
+            "opt-level=2",
+            "-"
+        ],
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,49 @@
+use rustflags::{Flag, RustdocFlag};
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+#[test]
+fn test_rustdoc_flags() {
+    let encoded = "--cfg\x1fdocsrs\x1f--document-private-items\x1f--html-in-header\x1fkatex.html\x1f--default-theme=ayu\x1f-Zunstable-options\x1f--generate-link-to-definition\x1f--extern-html-root-url\x1fserde=https://docs.rs/serde/1\x1f--crate-version\x1f1.2.3\x1f-o\x1ftarget/doc";
+    let flags: Vec<RustdocFlag> = rustflags::from_encoded_rustdoc(OsStr::new(encoded)).collect();
+    assert_eq!(
+        flags,
+        [
+            RustdocFlag::Rustc(Flag::Cfg {
+                name: "docsrs".to_owned(),
+                value: None,
+            }),
+            RustdocFlag::DocumentPrivateItems,
+            RustdocFlag::HtmlInHeader(PathBuf::from("katex.html")),
+            RustdocFlag::DefaultTheme("ayu".to_owned()),
+            RustdocFlag::Rustc(Flag::Z("unstable-options".to_owned())),
+            RustdocFlag::GenerateLinkToDefinition,
+            RustdocFlag::ExternHtmlRootUrl {
+                name: "serde".to_owned(),
+                url: "https://docs.rs/serde/1".to_owned(),
+            },
+            RustdocFlag::CrateVersion("1.2.3".to_owned()),
+            RustdocFlag::Rustc(Flag::OutDir(PathBuf::from("target/doc"))),
+        ],
+    );
+
+    let rendered: Vec<OsString> = flags.into_iter().flatten().collect();
+    let reparsed: Vec<RustdocFlag> =
+        rustflags::from_encoded_rustdoc(&rendered.join(OsStr::new("\x1f"))).collect();
+    assert_eq!(reparsed.len(), 9);
+    assert_eq!(reparsed[2].to_string(), "--html-in-header katex.html");
+}
+
+#[test]
+fn test_rustdoc_from_env() {
+    let flags: Vec<RustdocFlag> = rustflags::from_env_rustdoc_with(|key: &str| {
+        (key == "CARGO_ENCODED_RUSTDOCFLAGS").then(|| OsString::from("--show-type-layout"))
+    })
+    .collect();
+    assert_eq!(flags, [RustdocFlag::ShowTypeLayout]);
+
+    // Rustdoc-only flags are not recognized as rustc flags.
+    let flags: Vec<Flag> =
+        rustflags::from_encoded(OsStr::new("--show-type-layout\x1f-Dwarnings")).collect();
+    assert_eq!(flags, [Flag::Deny("warnings".to_owned())]);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use rustflags::{Flag, RustcInvocation};
+use std::ffi::OsStr;
+
+fn invocation(args: &[&str]) -> RustcInvocation {
+    RustcInvocation::parse(args).unwrap()
+}
+
+fn native(flag: &Flag) -> bool {
+    matches!(flag, Flag::Codegen { opt, value: Some(value) } if opt == "target-cpu" && value == "native")
+}
+
+const ARGS: &[&str] = &[
+    "/toolchain/bin/rustc",
+    "--crate-name",
+    "demo",
+    "--edition=2021",
+    "src/lib.rs",
+    "--error-format=json",
+    "--json=diagnostic-rendered-ansi,artifacts,future-incompat",
+    "--diagnostic-width=100",
+    "--crate-type",
+    "lib",
+    "-C",
+    "target-cpu=native",
+    "-Copt-level=3",
+    "--cfg",
+    "feature=\"std\"",
+    "-vV",
+];
+
+#[test]
+fn test_untouched() {
+    let rustc = invocation(ARGS);
+    assert_eq!(rustc.rustc(), OsStr::new("/toolchain/bin/rustc"));
+    assert_eq!(rustc.args(), ARGS[1..]);
+    assert_eq!(rustc.inputs().collect::<Vec<_>>(), ["src/lib.rs"]);
+    assert_eq!(rustc.flags().count(), 10);
+}
+
+#[test]
+fn test_edit() {
+    let mut rustc = invocation(ARGS);
+    rustc.retain(|flag| !native(flag));
+    rustc.map(|flag| match flag {
+        Flag::Edition(_) => Flag::Edition(2024),
+        flag => flag,
+    });
+    rustc.push(Flag::Codegen {
+        opt: "force-frame-pointers".to_owned(),
+        value: Some("yes".to_owned()),
+    });
+    assert_eq!(
+        rustc.args(),
+        [
+            "--crate-name",
+            "demo",
+            "--edition",
+            "2024",
+            "src/lib.rs",
+            "--error-format=json",
+            "--json=diagnostic-rendered-ansi,artifacts,future-incompat",
+            "--diagnostic-width=100",
+            "--crate-type",
+            "lib",
+            "-Copt-level=3",
+            "--cfg",
+            "feature=\"std\"",
+            "-vV",
+            "-C",
+            "force-frame-pointers=yes",
+        ],
+    );
+}
+
+#[test]
+fn test_partial_edit() {
+    let mut rustc = invocation(&["rustc", "--crate-type=lib,rlib", "-vV", "-"]);
+    rustc.retain(|flag| !matches!(flag, Flag::Version));
+    assert_eq!(rustc.args(), ["--crate-type=lib,rlib", "--verbose", "-"]);
+    assert_eq!(rustc.inputs().collect::<Vec<_>>(), ["-"]);
+}
+
+#[test]
+fn test_no_rustc() {
+    let error = RustcInvocation::parse(Vec::<String>::new()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "missing path of rustc in wrapper arguments"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = common::tempdir("wrapper");
+    let stub = dir.join("rustc");
+    fs::write(
+        &stub,
+        format!(
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > {}/args\n",
+            dir.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut rustc = RustcInvocation::parse([
+        stub.as_os_str(),
+        OsStr::new("-C"),
+        OsStr::new("target-cpu=native"),
+        OsStr::new("main.rs"),
+    ])
+    .unwrap();
+    rustc.retain(|flag| !native(flag));
+    assert!(rustc.command().status().unwrap().success());
+    assert_eq!(fs::read_to_string(dir.join("args")).unwrap(), "main.rs\n");
+
+    let _ = fs::remove_dir_all(&dir);
+}